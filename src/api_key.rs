@@ -0,0 +1,139 @@
+// API 金鑰驗證中介軟體：檢查 X-API-Key 標頭是否與 API_KEY 環境變數相符
+// 未設定 API_KEY 時允許所有請求通過，方便本地開發
+use actix_web::body::EitherBody;
+use actix_web::dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::{Error, HttpResponse};
+use sha2::{Digest, Sha256};
+use std::future::{ready, Future, Ready};
+use std::pin::Pin;
+use std::rc::Rc;
+
+use crate::ErrorResponse;
+
+// 不需要驗證的路徑：健康檢查及 Swagger UI
+fn is_exempt(path: &str) -> bool {
+    path == "/health"
+        || path == "/ready"
+        || path.starts_with("/swagger-ui")
+        || path.starts_with("/api-docs")
+}
+
+// 金鑰的 SHA-256 摘要，比較固定長度的摘要避免洩漏金鑰長度
+type KeyDigest = [u8; 32];
+
+fn digest(key: &[u8]) -> KeyDigest {
+    Sha256::digest(key).into()
+}
+
+// 以固定時間比較兩個摘要：逐位元組 XOR 後合併，不會在第一個不同的位元組提前返回
+fn digests_equal(a: &KeyDigest, b: &KeyDigest) -> bool {
+    a.iter().zip(b.iter()).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+#[derive(Clone)]
+pub struct ApiKey {
+    key: Option<Rc<KeyDigest>>, // 預期金鑰的摘要，None 表示不啟用驗證
+}
+
+impl ApiKey {
+    pub fn new(key: Option<String>) -> Self {
+        ApiKey {
+            key: key.map(|key| Rc::new(digest(key.as_bytes()))),
+        }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for ApiKey
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = ApiKeyMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(ApiKeyMiddleware {
+            service,
+            key: self.key.clone(),
+        }))
+    }
+}
+
+pub struct ApiKeyMiddleware<S> {
+    service: S,
+    key: Option<Rc<KeyDigest>>,
+}
+
+impl<S, B> Service<ServiceRequest> for ApiKeyMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let authorized = match &self.key {
+            None => true, // 未設定 API_KEY 時不驗證
+            Some(key) => req
+                .headers()
+                .get("X-API-Key")
+                .is_some_and(|value| digests_equal(&digest(value.as_bytes()), key)),
+        };
+
+        if authorized || is_exempt(req.path()) {
+            let fut = self.service.call(req);
+            return Box::pin(async move { fut.await.map(ServiceResponse::map_into_left_body) });
+        }
+
+        let response = HttpResponse::Unauthorized().json(ErrorResponse::new("unauthorized", "missing or invalid API key")); // 返回 401 Unauthorized 響應
+        Box::pin(ready(Ok(req.into_response(response).map_into_right_body())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::test::{call_service, init_service, TestRequest};
+    use actix_web::{web, App};
+
+    #[test]
+    fn digests_compare_by_content() {
+        assert!(digests_equal(&digest(b"secret"), &digest(b"secret")));
+        assert!(!digests_equal(&digest(b"secret"), &digest(b"secreT")));
+        assert!(!digests_equal(&digest(b"secret"), &digest(b"")));
+    }
+
+    #[actix_web::test]
+    async fn requires_matching_key() {
+        let app = init_service(
+            App::new()
+                .wrap(ApiKey::new(Some("secret".to_string())))
+                .route("/items", web::get().to(HttpResponse::Ok))
+                .route("/health", web::get().to(HttpResponse::Ok)),
+        )
+        .await;
+
+        let req = TestRequest::get().uri("/items").insert_header(("X-API-Key", "secret")).to_request();
+        assert_eq!(call_service(&app, req).await.status(), 200);
+        let req = TestRequest::get().uri("/items").insert_header(("X-API-Key", "wrong")).to_request();
+        assert_eq!(call_service(&app, req).await.status(), 401);
+        let req = TestRequest::get().uri("/items").to_request();
+        assert_eq!(call_service(&app, req).await.status(), 401);
+        let req = TestRequest::get().uri("/health").to_request();
+        assert_eq!(call_service(&app, req).await.status(), 200);
+    }
+
+    #[actix_web::test]
+    async fn open_without_key() {
+        let app = init_service(App::new().wrap(ApiKey::new(None)).route("/items", web::get().to(HttpResponse::Ok))).await;
+        let req = TestRequest::get().uri("/items").to_request();
+        assert_eq!(call_service(&app, req).await.status(), 200);
+    }
+}
@@ -0,0 +1,48 @@
+// 稽核日誌：每次創建、修改或刪除項目時，在 AUDIT_LOG 指定的文件結尾附加一行 JSON
+// 與資料文件分開保存，只會附加內容，不會清空或覆寫既有的記錄
+use chrono::Utc;
+use serde::Serialize;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::Mutex;
+
+// 稽核日誌中的一行記錄
+#[derive(Serialize)]
+struct AuditEntry<'a> {
+    timestamp: String,       // 修改時間（UTC，RFC3339）
+    method: &'a str,         // 造成修改的 HTTP 方法
+    id: usize,               // 被修改的項目 ID
+    before: Option<&'a str>, // 修改前的名稱，創建或還原時為 null
+    after: Option<&'a str>,  // 修改後的名稱，刪除時為 null
+}
+
+pub struct AuditLog {
+    file: Mutex<File>, // 以附加模式開啟的文件，寫入時需獨佔以免多行記錄交錯
+}
+
+impl AuditLog {
+    // 以附加模式開啟稽核日誌，文件不存在時自動建立
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(AuditLog { file: Mutex::new(file) })
+    }
+
+    // 附加一行記錄
+    pub fn append(&self, method: &str, id: usize, before: Option<&str>, after: Option<&str>) -> io::Result<()> {
+        let entry = AuditEntry {
+            timestamp: Utc::now().to_rfc3339(),
+            method,
+            id,
+            before,
+            after,
+        };
+        let mut line = serde_json::to_vec(&entry)?;
+        line.push(b'\n');
+        let mut file = self
+            .file
+            .lock()
+            .map_err(|_| io::Error::other("audit log is unavailable"))?;
+        file.write_all(&line) // 以單次寫入附加整行
+    }
+}
@@ -0,0 +1,190 @@
+// 統一響應格式：請求帶有 ?envelope=true 時，將 JSON 響應包裝為
+// 成功 { "data": ..., "error": null } 或失敗 { "data": null, "error": { "status", "code", "message" } }
+// 失敗時沿用 ErrorResponse 中機器可讀的 code，客戶端不需區分是否使用 envelope
+use actix_web::body::{to_bytes, BoxBody, EitherBody, MessageBody};
+use actix_web::dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::http::{header, StatusCode};
+use actix_web::{web, Error, HttpRequest, HttpResponse, Responder};
+use serde::{Deserialize, Serialize};
+use std::future::{ready, Future, Ready};
+use std::pin::Pin;
+
+// 失敗響應中的錯誤內容
+#[derive(Serialize)]
+pub struct EnvelopeError {
+    status: u16,     // HTTP 狀態碼
+    code: String,    // 機器可讀的錯誤代碼，與 ErrorResponse 的 code 相同，例如 not_found
+    message: String, // 錯誤描述
+    #[serde(skip_serializing_if = "Option::is_none")]
+    detail: Option<String>, // 錯誤的詳細資訊（可選）
+}
+
+// 包裝後的響應內容，data 與 error 只會有一個不是 null
+#[derive(Serialize)]
+pub struct Envelope<T: Serialize> {
+    data: Option<T>,
+    error: Option<EnvelopeError>,
+    #[serde(skip)]
+    status: StatusCode, // 響應的狀態碼
+}
+
+impl<T: Serialize> Envelope<T> {
+    // 成功的響應
+    pub fn data(status: StatusCode, data: Option<T>) -> Self {
+        Envelope {
+            data,
+            error: None,
+            status,
+        }
+    }
+
+    // 失敗的響應，body 為原本的 ErrorResponse 內容
+    // 沒有內容時（例如未註冊的路徑）以狀態碼的說明產生代碼及描述，例如 not_found 及 Not Found
+    pub fn error(status: StatusCode, body: Option<&serde_json::Value>) -> Self {
+        let field = |name: &str| body.and_then(|v| v.get(name)).and_then(|v| v.as_str()).map(str::to_string);
+        let reason = status.canonical_reason().unwrap_or_default();
+        Envelope {
+            data: None,
+            error: Some(EnvelopeError {
+                status: status.as_u16(),
+                code: field("code").unwrap_or_else(|| reason.to_lowercase().replace([' ', '-'], "_")),
+                message: field("message").unwrap_or_else(|| reason.to_string()),
+                detail: field("detail"),
+            }),
+            status,
+        }
+    }
+}
+
+impl<T: Serialize> Responder for Envelope<T> {
+    type Body = BoxBody;
+
+    fn respond_to(self, _req: &HttpRequest) -> HttpResponse<Self::Body> {
+        HttpResponse::build(self.status).json(&self)
+    }
+}
+
+// 是否要求包裝響應的查詢參數
+#[derive(Deserialize)]
+struct EnvelopeQuery {
+    envelope: Option<bool>,
+}
+
+// 只包裝 JSON 或沒有內容的響應，NDJSON、CSV 及事件串流維持原樣
+fn is_wrappable(response: &HttpResponse<impl MessageBody>) -> bool {
+    if matches!(response.status(), StatusCode::NO_CONTENT | StatusCode::NOT_MODIFIED) {
+        return false; // 這些狀態碼不能帶有內容
+    }
+    match response.headers().get(header::CONTENT_TYPE) {
+        None => true,
+        Some(value) => value.to_str().is_ok_and(|v| v.starts_with("application/json")),
+    }
+}
+
+pub struct EnvelopeResponses;
+
+impl<S, B> Transform<S, ServiceRequest> for EnvelopeResponses
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = EnvelopeResponsesMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(EnvelopeResponsesMiddleware { service }))
+    }
+}
+
+pub struct EnvelopeResponsesMiddleware<S> {
+    service: S,
+}
+
+impl<S, B> Service<ServiceRequest> for EnvelopeResponsesMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let enabled = web::Query::<EnvelopeQuery>::from_query(req.query_string())
+            .is_ok_and(|query| query.envelope.unwrap_or(false));
+        let fut = self.service.call(req);
+
+        Box::pin(async move {
+            let res = fut.await?;
+            if !enabled || !is_wrappable(res.response()) {
+                return Ok(res.map_into_left_body());
+            }
+
+            let (req, res) = res.into_parts();
+            let status = res.status();
+            let (head, body) = res.into_parts();
+            let bytes = to_bytes(body)
+                .await
+                .map_err(|e| actix_web::error::ErrorInternalServerError(e.into()))?; // 讀取原本的響應內容
+            let value: Option<serde_json::Value> = serde_json::from_slice(&bytes).ok();
+
+            let envelope = if status.is_success() {
+                Envelope::data(status, value)
+            } else {
+                Envelope::error(status, value.as_ref()) // 沿用 ErrorResponse 中的錯誤代碼及描述
+            };
+
+            let mut response = envelope.respond_to(&req);
+            for (name, value) in head.headers() {
+                if name != header::CONTENT_TYPE && name != header::CONTENT_LENGTH {
+                    response.headers_mut().append(name.clone(), value.clone()); // 保留 ETag、Location 等標頭
+                }
+            }
+            Ok(ServiceResponse::new(req, response).map_into_right_body())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ApiError;
+    use actix_web::test::{call_and_read_body_json, init_service, TestRequest};
+    use actix_web::App;
+
+    async fn not_found() -> Result<HttpResponse, ApiError> {
+        Err(ApiError::NotFound)
+    }
+
+    #[actix_web::test]
+    async fn wraps_data_and_errors() {
+        let app = init_service(
+            App::new()
+                .wrap(EnvelopeResponses)
+                .route("/items/1", web::get().to(|| async { HttpResponse::Ok().json(serde_json::json!({"id": 1})) }))
+                .route("/items/2", web::get().to(not_found)),
+        )
+        .await;
+
+        let req = TestRequest::get().uri("/items/1?envelope=true").to_request();
+        let body: serde_json::Value = call_and_read_body_json(&app, req).await;
+        assert_eq!(body, serde_json::json!({"data": {"id": 1}, "error": null}));
+
+        let req = TestRequest::get().uri("/items/2?envelope=true").to_request();
+        let body: serde_json::Value = call_and_read_body_json(&app, req).await;
+        assert_eq!(body["data"], serde_json::Value::Null);
+        assert_eq!(body["error"]["status"], 404);
+        assert_eq!(body["error"]["code"], "not_found");
+        assert_eq!(body["error"]["message"], "item not found");
+
+        let req = TestRequest::get().uri("/missing?envelope=true").to_request();
+        let body: serde_json::Value = call_and_read_body_json(&app, req).await;
+        assert_eq!(body["error"]["code"], "not_found");
+        assert_eq!(body["error"]["message"], "Not Found");
+    }
+}
@@ -0,0 +1,111 @@
+// 統一的錯誤類型：處理函式返回 Result<_, ApiError>，以 ? 傳遞錯誤
+// 每個錯誤都會轉換為對應的狀態碼及 { "code", "message" } 格式的 JSON 內容
+use actix_web::http::StatusCode;
+use actix_web::{HttpResponse, ResponseError};
+use serde::Serialize;
+use std::fmt;
+use utoipa::ToSchema;
+
+// 錯誤響應的 JSON 內容
+#[derive(Serialize, ToSchema)]
+pub struct ErrorResponse {
+    pub code: String,    // 機器可讀的錯誤代碼，例如 not_found
+    pub message: String, // 錯誤描述
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>, // 錯誤的詳細資訊（可選）
+}
+
+impl ErrorResponse {
+    pub fn new(code: &str, message: impl Into<String>) -> Self {
+        ErrorResponse {
+            code: code.to_string(),
+            message: message.into(),
+            detail: None,
+        }
+    }
+}
+
+// 處理函式可能返回的錯誤
+#[derive(Debug)]
+pub enum ApiError {
+    Validation(String),          // 請求內容或參數無效（400）
+    NotFound,                    // 項目不存在（404）
+    Conflict(String),            // 與現有項目衝突（409）
+    PreconditionFailed,          // If-Match 與目前的 ETag 不符（412）
+    PayloadTooLarge(String),     // 請求內容超過大小限制（413）
+    InsufficientStorage(String), // 項目數量已達上限（507）
+    Internal(String),            // 伺服器內部錯誤（500）
+}
+
+impl ApiError {
+    // 機器可讀的錯誤代碼
+    pub fn code(&self) -> &'static str {
+        match self {
+            ApiError::Validation(_) => "validation_error",
+            ApiError::NotFound => "not_found",
+            ApiError::Conflict(_) => "conflict",
+            ApiError::PreconditionFailed => "precondition_failed",
+            ApiError::PayloadTooLarge(_) => "payload_too_large",
+            ApiError::InsufficientStorage(_) => "insufficient_storage",
+            ApiError::Internal(_) => "internal_error",
+        }
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ApiError::Validation(message)
+            | ApiError::Conflict(message)
+            | ApiError::PayloadTooLarge(message)
+            | ApiError::InsufficientStorage(message)
+            | ApiError::Internal(message) => f.write_str(message),
+            ApiError::NotFound => f.write_str("item not found"),
+            ApiError::PreconditionFailed => f.write_str("item has been modified, ETag does not match"),
+        }
+    }
+}
+
+impl ResponseError for ApiError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            ApiError::Validation(_) => StatusCode::BAD_REQUEST,
+            ApiError::NotFound => StatusCode::NOT_FOUND,
+            ApiError::Conflict(_) => StatusCode::CONFLICT,
+            ApiError::PreconditionFailed => StatusCode::PRECONDITION_FAILED,
+            ApiError::PayloadTooLarge(_) => StatusCode::PAYLOAD_TOO_LARGE,
+            ApiError::InsufficientStorage(_) => StatusCode::INSUFFICIENT_STORAGE,
+            ApiError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        HttpResponse::build(self.status_code()).json(ErrorResponse::new(self.code(), self.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::body::to_bytes;
+
+    #[actix_web::test]
+    async fn each_variant_has_its_status_and_body() {
+        let cases = [
+            (ApiError::Validation("name must not be empty".to_string()), 400, "validation_error", "name must not be empty"),
+            (ApiError::NotFound, 404, "not_found", "item not found"),
+            (ApiError::Conflict("item with id 1 already exists".to_string()), 409, "conflict", "item with id 1 already exists"),
+            (ApiError::PreconditionFailed, 412, "precondition_failed", "item has been modified, ETag does not match"),
+            (ApiError::PayloadTooLarge("too large".to_string()), 413, "payload_too_large", "too large"),
+            (ApiError::InsufficientStorage("item limit of 3 reached".to_string()), 507, "insufficient_storage", "item limit of 3 reached"),
+            (ApiError::Internal("item store is unavailable".to_string()), 500, "internal_error", "item store is unavailable"),
+        ];
+        for (error, status, code, message) in cases {
+            let response = error.error_response();
+            assert_eq!(response.status().as_u16(), status);
+            let body = to_bytes(response.into_body()).await.unwrap();
+            let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            assert_eq!(body, serde_json::json!({"code": code, "message": message})); // 沒有 detail 時省略該欄位
+        }
+    }
+}
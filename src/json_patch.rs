@@ -0,0 +1,146 @@
+// JSON Patch（RFC 6902）：依序套用 add、remove、replace、move、copy 及 test 操作
+// 路徑使用 JSON Pointer（RFC 6901），例如 /name 或 /tags/0，任何一個操作失敗時返回錯誤描述
+use serde::Deserialize;
+use serde_json::Value;
+
+// 單一 patch 操作，op 不在支援範圍內時無法解析
+#[derive(Deserialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+enum Operation {
+    Add { path: String, value: Value },
+    Remove { path: String },
+    Replace { path: String, value: Value },
+    Move { from: String, path: String },
+    Copy { from: String, path: String },
+    Test { path: String, value: Value },
+}
+
+// 將 patch 陣列套用到目標，呼叫者應在副本上套用，失敗時捨棄結果
+pub fn apply(target: &mut Value, patch: &Value) -> Result<(), String> {
+    let operations: Vec<Operation> = serde_json::from_value(patch.clone()).map_err(|e| e.to_string())?;
+    for operation in operations {
+        match operation {
+            Operation::Add { path, value } => add(target, &path, value)?,
+            Operation::Remove { path } => {
+                remove(target, &path)?;
+            }
+            Operation::Replace { path, value } => {
+                *target.pointer_mut(&path).ok_or_else(|| missing(&path))? = value;
+            }
+            Operation::Move { from, path } => {
+                if path.starts_with(&format!("{}/", from)) {
+                    return Err(format!("cannot move {} into its own child {}", from, path));
+                }
+                let value = remove(target, &from)?;
+                add(target, &path, value)?;
+            }
+            Operation::Copy { from, path } => {
+                let value = target.pointer(&from).cloned().ok_or_else(|| missing(&from))?;
+                add(target, &path, value)?;
+            }
+            Operation::Test { path, value } => {
+                if target.pointer(&path) != Some(&value) {
+                    return Err(format!("test failed at {}", path));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn missing(path: &str) -> String {
+    format!("path {} does not exist", path)
+}
+
+// 將路徑拆成父節點的路徑及最後一段（已還原 ~1 及 ~0 跳脫）
+fn split_pointer(path: &str) -> Result<(&str, String), String> {
+    let index = path
+        .rfind('/')
+        .filter(|_| path.starts_with('/'))
+        .ok_or_else(|| format!("invalid path {:?}, must start with /", path))?;
+    let key = path[index + 1..].replace("~1", "/").replace("~0", "~");
+    Ok((&path[..index], key))
+}
+
+// 陣列索引必須是不超過 len 的十進位數字
+fn array_index(key: &str, len: usize, path: &str) -> Result<usize, String> {
+    key.parse::<usize>()
+        .ok()
+        .filter(|index| *index <= len && (key == "0" || !key.starts_with('0')))
+        .ok_or_else(|| format!("invalid array index in path {}", path))
+}
+
+fn add(target: &mut Value, path: &str, value: Value) -> Result<(), String> {
+    if path.is_empty() {
+        *target = value; // 空路徑代表整個文件
+        return Ok(());
+    }
+    let (parent, key) = split_pointer(path)?;
+    match target.pointer_mut(parent).ok_or_else(|| missing(parent))? {
+        Value::Object(map) => {
+            map.insert(key, value);
+        }
+        Value::Array(array) if key == "-" => array.push(value), // - 代表附加到陣列結尾
+        Value::Array(array) => {
+            let index = array_index(&key, array.len(), path)?;
+            array.insert(index, value);
+        }
+        _ => return Err(format!("parent of path {} is not an object or array", path)),
+    }
+    Ok(())
+}
+
+fn remove(target: &mut Value, path: &str) -> Result<Value, String> {
+    let (parent, key) = split_pointer(path)?;
+    match target.pointer_mut(parent).ok_or_else(|| missing(parent))? {
+        Value::Object(map) => map.remove(&key).ok_or_else(|| missing(path)),
+        Value::Array(array) => {
+            let index = array_index(&key, array.len(), path)?;
+            if index == array.len() {
+                return Err(missing(path)); // 插入時可以使用 len，移除時必須是現有的元素
+            }
+            Ok(array.remove(index))
+        }
+        _ => Err(missing(path)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn replaces_the_name() {
+        let mut item = json!({"id": 1, "name": "old", "tags": []});
+        apply(&mut item, &json!([{"op": "replace", "path": "/name", "value": "new"}])).unwrap();
+        assert_eq!(item, json!({"id": 1, "name": "new", "tags": []}));
+    }
+
+    #[test]
+    fn applies_operations_in_order() {
+        let mut item = json!({"name": "a", "tags": ["x"]});
+        let patch = json!([
+            {"op": "test", "path": "/name", "value": "a"},
+            {"op": "add", "path": "/tags/-", "value": "y"},
+            {"op": "add", "path": "/tags/0", "value": "w"},
+            {"op": "copy", "from": "/name", "path": "/title"},
+            {"op": "move", "from": "/title", "path": "/label"},
+            {"op": "remove", "path": "/tags/1"}
+        ]);
+        apply(&mut item, &patch).unwrap();
+        assert_eq!(item, json!({"name": "a", "tags": ["w", "y"], "label": "a"}));
+    }
+
+    #[test]
+    fn reports_failed_operations() {
+        let mut item = json!({"name": "a", "tags": []});
+        assert!(apply(&mut item, &json!([{"op": "replace", "path": "/missing", "value": 1}])).is_err());
+        assert!(apply(&mut item, &json!([{"op": "test", "path": "/name", "value": "b"}])).is_err());
+        assert!(apply(&mut item, &json!([{"op": "remove", "path": "/tags/0"}])).is_err());
+        assert!(apply(&mut item, &json!([{"op": "add", "path": "/tags/01", "value": 1}])).is_err());
+        assert!(apply(&mut item, &json!([{"op": "move", "from": "/tags", "path": "/tags/0"}])).is_err());
+        assert!(apply(&mut item, &json!([{"op": "rename", "path": "/name"}])).is_err());
+        assert!(apply(&mut item, &json!([{"op": "add", "path": "name", "value": 1}])).is_err());
+    }
+}
@@ -1,115 +1,2397 @@
 // use actix_web::web::Json;
-use actix_web::{delete, get, post, put, web, App, HttpResponse, HttpServer, Responder};
+
+use actix_cors::Cors;
+use actix_web::middleware::{Compress, Logger};
+use actix_web::error::JsonPayloadError;
+use actix_web::dev::RequestHead;
+use actix_web::http::header::{self, ContentEncoding};
+use actix_web::http::Method;
+use actix_web::{
+    delete, get, patch, post, put, route, web, App, HttpMessage, HttpRequest, HttpResponse, HttpResponseBuilder, HttpServer,
+    Responder,
+};
 use serde::{Deserialize, Serialize};
-use std::fs::{File, OpenOptions};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs::{self, File, OpenOptions};
 use std::io::{self, Read, Write};
-use std::path::Path;
-use std::sync::Mutex;
-use utoipa::{OpenApi, ToSchema};
+use std::path::{Path, PathBuf};
+use std::pin::pin;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use utoipa::{IntoParams, OpenApi, ToSchema};
 use utoipa_swagger_ui::SwaggerUi;
 use chrono::prelude::*;
+use chrono_tz::Tz;
+use fs2::FileExt;
+use actix_multipart::Multipart;
+use futures_util::future::{self, Either};
+use futures_util::{stream, TryStreamExt};
+use tokio::sync::broadcast;
+use sha2::{Digest, Sha256};
+use rand::seq::SliceRandom;
 use sysinfo::{CpuRefreshKind, RefreshKind, System};
 
-// 定義資料模型的結構
+mod api_key;
+mod audit;
+mod envelope;
+mod error;
+mod json_patch;
+mod maintenance;
+mod migration;
+mod rate_limit;
+mod read_only;
+mod request_id;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+mod store;
+#[cfg(test)]
+mod tests;
+mod timeout;
+#[cfg(feature = "tls")]
+mod tls;
+
+use api_key::ApiKey;
+use audit::AuditLog;
+use envelope::EnvelopeResponses;
+use error::{ApiError, ErrorResponse};
+use maintenance::{Maintenance, MaintenanceMode};
+use migration::SCHEMA_VERSION;
+use rate_limit::{RateLimit, RateLimiter};
+use read_only::ReadOnly;
+use request_id::RequestIdHeader;
+use store::ItemStore;
+use timeout::RequestTimeout;
+
+// 定義資料模型的結構，啟用 camel-case feature 時欄位名稱改為 camelCase（例如 createdAt）
+// 並接受舊的 snake_case 名稱，讓原本的資料文件仍可載入
 #[derive(Serialize, Deserialize, Clone, ToSchema)]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
 struct Item {
     id: usize,      // 項目的唯一識別 ID
     name: String,   // 項目的名稱
+    #[serde(default)]
+    #[cfg_attr(feature = "camel-case", serde(alias = "created_at"))]
+    created_at: DateTime<Local>, // 項目的創建時間（RFC3339），舊資料缺少時使用預設值
+    #[serde(default)]
+    #[cfg_attr(feature = "camel-case", serde(alias = "updated_at"))]
+    updated_at: DateTime<Local>, // 項目的最後修改時間（RFC3339）
+    #[serde(default)]
+    deleted: bool, // 是否已被軟刪除，保留在文件中以便之後還原或清除
+    #[serde(default)]
+    tags: Vec<String>, // 項目的分類標籤，舊資料缺少時為空
+    #[serde(default = "initial_version")]
+    version: u64, // 最後一次修改時的全域變更版本號，用於偵測並行修改及增量同步，舊資料缺少時為 1
+    #[serde(default)]
+    #[cfg_attr(feature = "camel-case", serde(alias = "parent_id"))]
+    parent_id: Option<usize>, // 父項目的 ID，沒有父項目時為 null
+}
+
+// 新項目的版本號
+fn initial_version() -> u64 {
+    1
+}
+
+// 項目中最大的版本號，用於初始化全域變更計數器
+fn max_version(items: &[Item]) -> u64 {
+    items.iter().map(|i| i.version).max().unwrap_or(0)
+}
+
+impl Item {
+    // 以目前時間作為創建及修改時間建立新項目
+    fn new(id: usize, name: String) -> Self {
+        let now = Local::now();
+        Item {
+            id,
+            name,
+            created_at: now,
+            updated_at: now,
+            deleted: false,
+            tags: vec![],
+            version: initial_version(),
+            parent_id: None,
+        }
+    }
+}
+
+// 創建項目時的請求內容，未提供 ID 時由伺服器分配
+#[derive(Deserialize, ToSchema)]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+struct NewItem {
+    id: Option<usize>, // 客戶端指定的 ID（可選）
+    #[serde(default)]
+    name: String, // 項目的名稱，設定 DEFAULT_NAME_PREFIX 時可以省略
+    #[serde(default)]
+    tags: Vec<String>, // 項目的分類標籤（可選），未提供時使用 DEFAULT_TAGS
+    #[cfg_attr(feature = "camel-case", serde(alias = "parent_id"))]
+    parent_id: Option<usize>, // 父項目的 ID（可選），父項目必須存在
+}
+
+// 部分更新項目時的請求內容，只更新提供的欄位
+// 實際以 JSON merge patch 處理請求內容，這個結構只用於 OpenAPI 文檔
+#[derive(ToSchema)]
+#[cfg_attr(feature = "camel-case", schema(rename_all = "camelCase"))]
+#[allow(dead_code)]
+struct ItemPatch {
+    name: Option<String>,      // 新的項目名稱（可選）
+    parent_id: Option<usize>, // 新的父項目 ID（可選），設為 null 時移除父項目
+}
+
+// 調整項目順序的請求內容
+#[derive(Deserialize, ToSchema)]
+struct ReorderItem {
+    position: usize, // 在未刪除項目中的新位置，從 0 開始，超過項目數時移到最後
+}
+
+// 依前綴批次重新命名項目的請求內容
+#[derive(Deserialize, ToSchema)]
+struct BulkRename {
+    prefix: String,      // 要替換的名稱前綴
+    replacement: String, // 取代前綴的新字串
+}
+
+// 批次刪除項目的請求內容
+#[derive(Deserialize, ToSchema)]
+struct BulkDelete {
+    ids: Vec<usize>, // 要刪除的項目 ID，不存在的 ID 會被忽略
+}
+
+// 修改項目時的預覽參數
+#[derive(Deserialize, IntoParams)]
+struct DryRunQuery {
+    dry_run: Option<bool>, // 為 true 時只返回會受影響的項目，不實際修改
+}
+
+// 重新載入資料文件的查詢參數
+#[derive(Deserialize, IntoParams)]
+struct ReloadQuery {
+    force: Option<bool>, // 為 true 時即使有尚未寫入文件的變更也重新載入，並捨棄這些變更
+}
+
+// 批次刪除項目的查詢參數
+#[derive(Deserialize, IntoParams)]
+struct BulkDeleteQuery {
+    dry_run: Option<bool>, // 為 true 時只返回會受影響的項目，不實際修改
+    confirm: Option<bool>, // 刪除的比例超過 BULK_DELETE_CONFIRM_RATIO 時必須為 true
+}
+
+// 預覽修改時的響應內容
+#[derive(Serialize, ToSchema)]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+struct DryRunResult {
+    dry_run: bool,    // 固定為 true，表示沒有實際修改
+    count: usize,     // 會受影響的項目數量
+    items: Vec<Item>, // 會受影響的項目，重新命名時為修改後的內容
+}
+
+// 匯入項目時的查詢參數
+#[derive(Deserialize, IntoParams)]
+struct ImportQuery {
+    mode: Option<String>, // replace：以文件內容取代所有項目，文件中沒有的項目會被軟刪除；merge：依 ID 更新或新增項目，預設為 merge
+    confirm: Option<bool>, // replace 刪除的比例超過 BULK_DELETE_CONFIRM_RATIO 時必須為 true
+}
+
+// 匯入文件允許的最大位元組數
+const MAX_IMPORT_BYTES: usize = 10 * 1024 * 1024;
+
+// NDJSON 匯入時單行允許的最大位元組數，請求內容以串流讀取，不限制總大小
+const MAX_NDJSON_LINE_BYTES: usize = 64 * 1024;
+
+// NDJSON 匯入時每累積多少行就在寫入鎖下套用一次，限制記憶體用量及持有寫入鎖的時間
+const NDJSON_BATCH_LINES: usize = 1000;
+
+// NDJSON 匯入的結果
+#[derive(Serialize, ToSchema)]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+struct NdjsonImportResult {
+    imported: usize,          // 成功匯入的項目數量
+    failed_lines: Vec<usize>, // 無法解析或項目無效而略過的行號（從 1 開始）
+}
+
+// 獲取項目列表的查詢參數
+#[derive(Deserialize, IntoParams)]
+struct ListQuery {
+    limit: Option<usize>,          // 每頁返回的最大項目數，預設為 50
+    offset: Option<usize>,         // 跳過的項目數，預設為 0
+    sort_by: Option<String>,       // 排序欄位：position、id 或 name，預設為 position（POST /items/{id}/reorder 調整的順序）
+    order: Option<String>,         // 排序方向：asc 或 desc，預設為 asc
+    include_deleted: Option<bool>, // 為 true 時包含已軟刪除的項目
+    name: Option<String>,          // 只返回名稱完全相符（區分大小寫）的項目
+    fields: Option<String>,        // 以逗號分隔要返回的欄位，例如 id,name，預設返回所有欄位
+    tag: Option<String>,           // 只返回包含此標籤的項目
+    from: Option<String>,          // 只返回在此時間（RFC3339）之後創建的項目，包含此時間
+    to: Option<String>,            // 只返回在此時間（RFC3339）之前創建的項目，包含此時間
+    paged: Option<bool>,           // 為 true 時返回包含分頁資訊的物件，而不是項目陣列
+}
+
+// 包含分頁資訊的項目列表，供 ?paged=true 使用
+#[derive(Serialize, ToSchema)]
+#[aliases(ItemPage = PagedItems<Item>)]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+struct PagedItems<T: Serialize> {
+    items: Vec<T>,  // 目前這一頁的項目
+    total: usize,   // 分頁前的項目總數
+    limit: usize,   // 每頁的最大項目數
+    offset: usize,  // 跳過的項目數
+    has_more: bool, // 之後是否還有項目
+}
+
+impl<T: Serialize> PagedItems<T> {
+    fn new(items: Vec<T>, total: usize, limit: usize, offset: usize) -> Self {
+        let has_more = offset + items.len() < total;
+        PagedItems { items, total, limit, offset, has_more }
+    }
+}
+
+// 項目列表的排序欄位
+enum SortField {
+    Position, // 項目在列表中的順序
+    Id,
+    Name,
+}
+
+// 解析排序參數，返回排序欄位及是否為降冪
+fn parse_sort(query: &ListQuery) -> Result<(SortField, bool), String> {
+    let field = match query.sort_by.as_deref().unwrap_or("position") {
+        "position" => SortField::Position,
+        "id" => SortField::Id,
+        "name" => SortField::Name,
+        other => return Err(format!("unknown sort_by value: {}", other)),
+    };
+    let descending = match query.order.as_deref().unwrap_or("asc") {
+        "asc" => false,
+        "desc" => true,
+        other => return Err(format!("unknown order value: {}", other)),
+    };
+    Ok((field, descending))
+}
+
+// 創建時間的篩選範圍，None 表示不限制
+type DateRange = (Option<DateTime<FixedOffset>>, Option<DateTime<FixedOffset>>);
+
+// 解析 from 及 to 參數，時間格式錯誤或 from 晚於 to 時返回錯誤
+fn parse_date_range(query: &ListQuery) -> Result<DateRange, String> {
+    let parse = |name: &str, value: &Option<String>| {
+        value
+            .as_deref()
+            .map(|v| DateTime::parse_from_rfc3339(v).map_err(|e| format!("invalid {} value {}: {}", name, v, e)))
+            .transpose()
+    };
+    let from = parse("from", &query.from)?;
+    let to = parse("to", &query.to)?;
+    if let (Some(from), Some(to)) = (from, to) {
+        if from > to {
+            return Err("from must not be later than to".to_string());
+        }
+    }
+    Ok((from, to))
+}
+
+// 項目可供選取的欄位名稱，與序列化後的名稱相同
+#[cfg(not(feature = "camel-case"))]
+const ITEM_FIELDS: [&str; 8] = ["id", "name", "created_at", "updated_at", "deleted", "tags", "version", "parent_id"];
+#[cfg(feature = "camel-case")]
+const ITEM_FIELDS: [&str; 8] = ["id", "name", "createdAt", "updatedAt", "deleted", "tags", "version", "parentId"];
+
+// 解析 fields 參數，返回要保留的欄位名稱
+fn parse_fields(fields: &str) -> Result<Vec<&str>, String> {
+    fields
+        .split(',')
+        .map(str::trim)
+        .map(|field| {
+            if ITEM_FIELDS.contains(&field) {
+                Ok(field)
+            } else {
+                Err(format!("unknown field: {}", field))
+            }
+        })
+        .collect()
+}
+
+// 只保留指定欄位，將項目轉換為 JSON 物件
+fn select_fields(item: &Item, fields: &[&str]) -> serde_json::Map<String, serde_json::Value> {
+    let mut object = match serde_json::to_value(item) {
+        Ok(serde_json::Value::Object(object)) => object,
+        _ => serde_json::Map::new(),
+    };
+    object.retain(|key, _| fields.contains(&key.as_str()));
+    object
+}
+
+// 更新項目時的查詢參數
+#[derive(Deserialize, IntoParams)]
+struct UpsertQuery {
+    upsert: Option<bool>,          // 為 true 時，項目不存在則以指定 ID 創建
+    expected_version: Option<u64>, // 指定時，項目目前的版本號不同則返回 409
+}
+
+// 名稱搜尋查詢參數
+#[derive(Deserialize, IntoParams)]
+struct SearchQuery {
+    q: String, // 要搜尋的名稱片段（不分大小寫）
+}
+
+// 名稱自動完成預設返回的數量
+const DEFAULT_AUTOCOMPLETE_LIMIT: usize = 10;
+
+// 名稱自動完成的查詢參數
+#[derive(Deserialize, IntoParams)]
+struct AutocompleteQuery {
+    #[serde(default)]
+    prefix: String,       // 名稱的開頭（不分大小寫），未提供時符合所有名稱
+    limit: Option<usize>, // 最多返回的名稱數量，預設為 10
+}
+
+// 一次獲取多個項目時最多可以指定的 ID 數量
+const MAX_BATCH_IDS: usize = 100;
+
+// 批次獲取項目的查詢參數
+#[derive(Deserialize, IntoParams)]
+struct BatchQuery {
+    ids: String, // 以逗號分隔的項目 ID，例如 1,2,3，最多 100 個
+}
+
+// 健康檢查響應的 JSON 內容
+#[derive(Serialize, ToSchema)]
+struct HealthStatus {
+    status: String, // 服務狀態
+}
+
+// 版本資訊響應的 JSON 內容
+#[derive(Serialize, ToSchema)]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+struct VersionInfo {
+    version: String,         // Cargo 套件版本
+    git_commit: String,      // 編譯時的 git commit，無法取得時為 unknown
+    build_timestamp: String, // 編譯時間（RFC3339）
+}
+
+// 項目數量響應的 JSON 內容
+#[derive(Serialize, ToSchema)]
+struct ItemCount {
+    count: usize, // 目前的項目數量
+}
+
+// 項目統計資訊的 JSON 內容，沒有項目時 latest_id 為 null，長度為 0
+#[derive(Serialize, ToSchema)]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+struct ItemStats {
+    count: usize,             // 未刪除的項目數量
+    latest_id: Option<usize>, // 最近創建的項目 ID
+    longest_name: usize,      // 最長的名稱長度（字元數）
+    shortest_name: usize,     // 最短的名稱長度（字元數）
+}
+
+impl ItemStats {
+    // 從項目列表計算統計資訊，已刪除的項目不計入
+    fn from_items(items: &[Item]) -> Self {
+        let active: Vec<&Item> = items.iter().filter(|i| !i.deleted).collect();
+        let name_lengths = active.iter().map(|i| i.name.chars().count());
+        ItemStats {
+            count: active.len(),
+            latest_id: active.iter().max_by_key(|i| (i.created_at, i.id)).map(|i| i.id), // 創建時間相同時取較大的 ID
+            longest_name: name_lengths.clone().max().unwrap_or(0),
+            shortest_name: name_lengths.min().unwrap_or(0),
+        }
+    }
+}
+
+// 增量同步的查詢參數
+#[derive(Deserialize, IntoParams)]
+struct ChangesQuery {
+    since: Option<u64>, // 上一次取得的 version 游標，未提供時返回所有項目
+}
+
+// 增量同步的響應內容
+#[derive(Serialize, ToSchema)]
+struct ItemChanges {
+    items: Vec<Item>, // version 大於 since 的項目，依 version 排序，包含已刪除的項目
+    version: u64,     // 目前的全域版本號，下次請求時作為 since
+}
+
+// 項目校驗碼的 JSON 內容
+#[derive(Serialize, ToSchema)]
+struct ItemChecksum {
+    sha256: String, // 以與資料文件相同格式序列化所有項目後的 SHA-256（十六進位）
+    count: usize,   // 計入校驗碼的項目數量，包含已刪除的項目
+}
+
+// 項目的精簡內容，用於列表畫面，Item 增加欄位時維持不變
+#[derive(Serialize, ToSchema)]
+struct ItemSummary {
+    id: usize,    // 項目 ID
+    name: String, // 項目名稱
+}
+
+impl From<&Item> for ItemSummary {
+    fn from(item: &Item) -> Self {
+        ItemSummary {
+            id: item.id,
+            name: item.name.clone(),
+        }
+    }
+}
+
+// OPTIONS 請求返回的資源說明
+#[derive(Serialize, ToSchema)]
+struct ResourceOptions {
+    path: String,                    // 請求的路徑
+    methods: Vec<MethodDescription>, // 支援的方法，唯讀模式下不包含修改資料的方法
+}
+
+// 資源支援的一個方法
+#[derive(Serialize, ToSchema)]
+struct MethodDescription {
+    method: String,      // HTTP 方法
+    description: String, // 方法的用途
+}
+
+// /items 支援的方法及說明
+const COLLECTION_METHODS: &[(&str, &str)] = &[
+    ("GET", "List items with filtering, sorting and pagination"),
+    ("HEAD", "Same headers as GET without a body"),
+    ("POST", "Create an item"),
+    ("DELETE", "Delete all items"),
+    ("OPTIONS", "Describe the supported methods"),
+];
+
+// /items/{id} 支援的方法及說明
+const ITEM_METHODS: &[(&str, &str)] = &[
+    ("GET", "Get the item"),
+    ("HEAD", "Same headers as GET without a body"),
+    ("PUT", "Replace the item, or create it with upsert=true"),
+    ("PATCH", "Partially update the item with a JSON merge patch or JSON Patch"),
+    ("DELETE", "Soft-delete the item"),
+    ("OPTIONS", "Describe the supported methods"),
+];
+
+// 沒有標籤的項目在依標籤分組結果中使用的鍵
+const UNTAGGED_KEY: &str = "__untagged__";
+
+// 依標籤分組的項目，鍵為標籤並依字母排序，有多個標籤的項目會出現在每個標籤下
+#[derive(Serialize, ToSchema)]
+struct ItemsByTag(BTreeMap<String, Vec<Item>>);
+
+// 系統資訊的查詢參數
+#[derive(Deserialize, IntoParams)]
+struct SystemInfoQuery {
+    tz: Option<String>, // IANA 時區名稱，例如 UTC 或 Asia/Taipei，未提供時使用伺服器本地時區
 }
 
 #[derive(Serialize)]
 struct Info {
     time: String,
+    utc: String,    // UTC 時間（RFC3339）
+    offset: String, // time 所在時區相對 UTC 的偏移，例如 +08:00
     ram: Ram,
-    cpu: CPU,
+    cpu: Cpu,
+}
+
+#[derive(Serialize)]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+struct Ram {
+    total_ram: u64,
+    usage_ram: u64,
+}
+
+#[derive(Serialize)]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+struct Cpu {
+    usage_cpu: f32,
+}
+
+// 項目操作的種類，用於請求計數
+enum Operation {
+    Create,
+    Read,
+    Update,
+    Delete,
+}
+
+// 請求計數器，由 /metrics 以 Prometheus 文字格式輸出
+#[derive(Default)]
+struct Metrics {
+    requests_total: AtomicU64,
+    creates_total: AtomicU64,
+    reads_total: AtomicU64,
+    updates_total: AtomicU64,
+    deletes_total: AtomicU64,
+}
+
+impl Metrics {
+    // 記錄一次請求，並增加對應操作的計數
+    fn record(&self, operation: Operation) {
+        self.requests_total.fetch_add(1, Ordering::Relaxed);
+        let counter = match operation {
+            Operation::Create => &self.creates_total,
+            Operation::Read => &self.reads_total,
+            Operation::Update => &self.updates_total,
+            Operation::Delete => &self.deletes_total,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+// 項目變更事件的種類
+#[derive(Serialize, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+enum ItemEventKind {
+    Created,
+    Updated,
+    Deleted,
+}
+
+// 透過 /items/events 推送給客戶端的項目變更事件
+#[derive(Serialize, Clone)]
+struct ItemEvent {
+    #[serde(rename = "type")]
+    kind: ItemEventKind, // 變更種類
+    id: usize,           // 變更的項目 ID
+}
+
+// 定義應用程式狀態，包含一個 RwLock 保護的 Vec<Item>，讀取可並行、寫入時獨佔
+struct AppState {
+    items: RwLock<ItemStore>, // 項目列表及 ID 索引
+    items_file: PathBuf,      // 資料文件的路徑
+    metrics: Metrics,         // 請求計數器
+    dirty: AtomicBool,        // 記憶體中的項目是否有尚未寫入文件的變更
+    last_modified: AtomicU64, // 項目最後一次變更的時間（Unix 秒數），用於 Last-Modified 標頭
+    change_version: AtomicU64, // 全域變更計數器，每次修改項目時遞增並寫入該項目的 version
+    events: broadcast::Sender<ItemEvent>, // 項目變更事件的廣播通道
+    idempotency_keys: Mutex<HashMap<String, (usize, Instant)>>, // Idempotency-Key 對應的項目 ID 及記錄時間
+    idempotency_ttl: Duration, // Idempotency-Key 的保留時間
+    persist: bool,             // 是否將項目寫入 JSON 文件，為 false 時只保存在記憶體中
+    save_retries: u32,         // 寫入 JSON 文件失敗時的重試次數
+    backup_keep: usize,        // 保留的資料文件備份數量，為 0 時不備份
+    unique_names: bool,        // 是否禁止多個項目使用相同的名稱
+    api_prefix: String,        // 項目服務的路徑前綴，用於 Location 標頭
+    defaults: ItemDefaults,    // 創建項目時套用的預設標籤及名稱前綴
+    name_normalization: NameNormalization, // 創建及更新項目時整理名稱的方式
+    audit_log: Option<AuditLog>, // 稽核日誌，未設定 AUDIT_LOG 時不記錄
+    file_mtime: Mutex<Option<SystemTime>>, // 最後一次讀取或寫入資料文件後的修改時間，用於偵測外部編輯
+    max_items: Option<usize>,  // 項目數量的上限，為 None 時不限制
+    read_only: bool,           // 是否為唯讀模式，OPTIONS 請求據此省略修改資料的方法
+    bulk_delete_confirm_ratio: f64, // 批次刪除超過此比例的項目時需要 confirm=true
+}
+
+// 目前時間的 Unix 秒數
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+impl AppState {
+    // 獲取讀取鎖，鎖已損壞時返回 500 錯誤
+    fn read_items(&self) -> Result<RwLockReadGuard<'_, ItemStore>, ApiError> {
+        self.items
+            .read()
+            .map_err(|_| ApiError::Internal("item store is unavailable".to_string()))
+    }
+
+    // 獲取寫入鎖，鎖已損壞時返回 500 錯誤
+    // 資料文件已被外部修改時返回 409，避免在過期的資料上繼續修改並覆蓋他人的編輯
+    fn write_items(&self) -> Result<RwLockWriteGuard<'_, ItemStore>, ApiError> {
+        let items = self
+            .items
+            .write()
+            .map_err(|_| ApiError::Internal("item store is unavailable".to_string()))?;
+        if self.file_changed_externally() {
+            return Err(ApiError::Conflict(format!(
+                "{} was modified externally, reload it with POST /admin/reload before making changes",
+                self.items_file.display()
+            )));
+        }
+        Ok(items)
+    }
+
+    // 資料文件的修改時間是否與最後一次讀取或寫入時不同
+    fn file_changed_externally(&self) -> bool {
+        self.persist
+            && self
+                .file_mtime
+                .lock()
+                .is_ok_and(|tracked| *tracked != file_mtime(&self.items_file))
+    }
+
+    // 記錄資料文件目前的修改時間，在讀取或寫入文件後呼叫
+    fn track_file_mtime(&self) {
+        if let Ok(mut tracked) = self.file_mtime.lock() {
+            *tracked = file_mtime(&self.items_file);
+        }
+    }
+
+    // 遞增全域變更計數器並返回新的版本號，需在持有寫入鎖時呼叫，讓版本號與修改順序一致
+    fn next_version(&self) -> u64 {
+        self.change_version.fetch_add(1, Ordering::AcqRel) + 1
+    }
+
+    // 檢查新增 adding 個項目後是否超過 MAX_ITEMS，需在持有寫入鎖時呼叫以免同時創建的請求一起超過上限
+    // 只計算未刪除的項目，軟刪除的項目不佔用上限
+    fn check_capacity(&self, items: &[Item], adding: usize) -> Result<(), ApiError> {
+        let active = items.iter().filter(|i| !i.deleted).count();
+        match self.max_items {
+            Some(max) if active + adding > max => Err(ApiError::InsufficientStorage(format!(
+                "item limit of {} reached, {} items are stored",
+                max, active
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    // 一次刪除超過 BULK_DELETE_CONFIRM_RATIO 比例的未刪除項目且未加上 confirm=true 時返回 400，避免誤刪大部分的資料
+    fn confirm_delete(&self, items: &[Item], deleting: usize, confirm: Option<bool>) -> Result<(), ApiError> {
+        let active = items.iter().filter(|i| !i.deleted).count();
+        if deleting as f64 > active as f64 * self.bulk_delete_confirm_ratio && !confirm.unwrap_or(false) {
+            return Err(ApiError::Validation(format!(
+                "deleting {} of {} items exceeds {}% of the items, repeat the request with confirm=true to proceed",
+                deleting,
+                active,
+                self.bulk_delete_confirm_ratio * 100.0
+            )));
+        }
+        Ok(())
+    }
+
+    // 在稽核日誌中記錄一次修改，寫入失敗時只記錄警告，不影響已完成的修改
+    fn audit(&self, method: &str, id: usize, before: Option<&str>, after: Option<&str>) {
+        if let Some(Err(e)) = self.audit_log.as_ref().map(|log| log.append(method, id, before, after)) {
+            log::warn!("failed to write audit log: {}", e);
+        }
+    }
+
+    // 標記項目已變更，由背景任務批次寫入 JSON 文件，並更新最後變更時間
+    fn mark_dirty(&self) {
+        self.last_modified.store(unix_now(), Ordering::Release);
+        self.dirty.store(true, Ordering::Release);
+    }
+
+    // 將項目寫入 JSON 文件，停用持久化時不寫入
+    // 文件在最後一次讀取或寫入後被外部修改時拒絕覆蓋，保留標記直到透過 POST /admin/reload 重新載入
+    // 檢查、寫入及記錄修改時間期間持有 file_mtime 的鎖，其他請求不會把伺服器自己寫入的文件誤認為外部修改
+    fn save(&self, items: &[Item]) -> io::Result<()> {
+        if !self.persist {
+            return Ok(());
+        }
+        let mut tracked = self
+            .file_mtime
+            .lock()
+            .map_err(|_| io::Error::other("file modification time is unavailable"))?;
+        if *tracked != file_mtime(&self.items_file) {
+            return Err(io::Error::other(format!(
+                "{} was modified externally, refusing to overwrite it",
+                self.items_file.display()
+            )));
+        }
+        with_retries(self.save_retries, || save_items(&self.items_file, items, self.backup_keep))?;
+        *tracked = file_mtime(&self.items_file);
+        Ok(())
+    }
+
+    // 廣播項目變更事件，沒有訂閱者時直接丟棄
+    fn publish(&self, kind: ItemEventKind, id: usize) {
+        let _ = self.events.send(ItemEvent { kind, id });
+    }
+
+    // 項目最後一次變更的時間
+    fn last_modified(&self) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(self.last_modified.load(Ordering::Acquire))
+    }
+
+    // 有尚未寫入的變更時寫入 JSON 文件，失敗時保留標記以便下次重試
+    // 只在複製項目時持有讀取鎖，寫入及重試等待期間不會阻擋其他請求；會阻塞執行緒，需透過 web::block 呼叫
+    fn flush_if_dirty(&self) -> io::Result<()> {
+        if !self.dirty.swap(false, Ordering::AcqRel) {
+            return Ok(());
+        }
+        let snapshot = match self.items.read() {
+            Ok(items) => items.to_vec(),
+            Err(_) => {
+                self.dirty.store(true, Ordering::Release);
+                return Err(io::Error::other("item store is unavailable"));
+            }
+        };
+        let result = self.save(&snapshot);
+        if result.is_err() {
+            self.dirty.store(true, Ordering::Release); // 只恢復寫入標記，項目本身並未變更
+        }
+        result
+    }
+}
+
+// 讀取以逗號分隔的環境變數，忽略空白及空的項目，未設定時返回空列表
+fn env_list(name: &str) -> Vec<String> {
+    std::env::var(name)
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+// 讀取並解析環境變數，未設定時返回 None，無法解析時返回錯誤
+fn parse_optional_env_var<T>(name: &str) -> io::Result<Option<T>>
+where
+    T: std::str::FromStr,
+    T::Err: std::fmt::Display,
+{
+    match std::env::var(name) {
+        Ok(value) => value.parse().map(Some).map_err(|e| {
+            io::Error::new(io::ErrorKind::InvalidInput, format!("invalid {} {:?}: {}", name, value, e))
+        }),
+        Err(_) => Ok(None),
+    }
+}
+
+// 讀取並解析環境變數，未設定時使用預設值，無法解析時返回錯誤
+fn parse_env_var<T>(name: &str, default: T) -> io::Result<T>
+where
+    T: std::str::FromStr,
+    T::Err: std::fmt::Display,
+{
+    Ok(parse_optional_env_var(name)?.unwrap_or(default))
+}
+
+// 伺服器的設定
+struct ServerConfig {
+    host: String,              // 綁定的 IP，預設為 127.0.0.1
+    port: u16,                 // 綁定的端口，預設為 8080
+    max_body_bytes: usize,     // JSON 請求內容的最大位元組數，預設為 64KB
+    rate_limit_per_min: u32,   // 每個 IP 每分鐘允許的請求數，預設為 0（不限流）
+    idempotency_ttl: Duration, // Idempotency-Key 的保留時間，預設為 24 小時
+    workers: Option<usize>,    // worker 執行緒數量，未設定時每個 CPU 核心一個
+    persist: bool,             // 是否讀寫 JSON 文件，預設為 true
+    tls: Option<TlsPaths>,     // TLS 憑證及私鑰的路徑，未設定時使用 HTTP
+    save_retries: u32,         // 寫入 JSON 文件失敗時的重試次數，預設為 3
+    backup_keep: usize,        // 保留的資料文件備份數量，預設為 5
+    unique_names: bool,        // 是否禁止重複的項目名稱，預設為 false
+    maintenance: bool,         // 是否在啟動時進入維護模式，預設為 false
+    read_only: bool,           // 是否拒絕所有修改資料的請求，預設為 false
+    request_timeout: Option<Duration>, // 每個請求的時間上限，預設為 30 秒，設為 0 時不限制
+    max_items: Option<usize>,  // 未刪除項目數量的上限，預設為 0（不限制）
+    bulk_delete_confirm_ratio: f64, // 批次刪除超過此比例的項目時需要 confirm=true，預設為 0.5
+    database_url: Option<String>, // SQLite 資料庫的位址，設定時改用 SQLite 後端
+}
+
+// TLS 憑證及私鑰的 PEM 文件路徑
+#[cfg_attr(not(feature = "tls"), allow(dead_code))]
+struct TlsPaths {
+    cert: PathBuf, // 憑證鏈（TLS_CERT）
+    key: PathBuf,  // 私鑰（TLS_KEY）
+}
+
+// 決定使用 HTTP 或 HTTPS：兩者都未設定時使用 HTTP，只設定其中一個時返回錯誤
+fn resolve_tls(cert: Option<String>, key: Option<String>) -> io::Result<Option<TlsPaths>> {
+    match (cert, key) {
+        (Some(cert), Some(key)) => Ok(Some(TlsPaths {
+            cert: PathBuf::from(cert),
+            key: PathBuf::from(key),
+        })),
+        (None, None) => Ok(None),
+        (Some(_), None) => Err(io::Error::new(io::ErrorKind::InvalidInput, "TLS_CERT is set but TLS_KEY is missing")),
+        (None, Some(_)) => Err(io::Error::new(io::ErrorKind::InvalidInput, "TLS_KEY is set but TLS_CERT is missing")),
+    }
+}
+
+impl ServerConfig {
+    // 從環境變數讀取設定，數值無法解析時返回錯誤
+    fn from_env() -> io::Result<Self> {
+        let host = std::env::var("HOST").unwrap_or_else(|_| "127.0.0.1".to_string());
+        let port = parse_env_var("PORT", 8080)?;
+        let max_body_bytes = parse_env_var("MAX_BODY_BYTES", 64 * 1024)?;
+        let rate_limit_per_min = parse_env_var("RATE_LIMIT_PER_MIN", 0)?;
+        let idempotency_ttl = Duration::from_secs(parse_env_var("IDEMPOTENCY_TTL_SECS", 24 * 60 * 60)?);
+        let workers = parse_optional_env_var("WORKERS")?;
+        if workers == Some(0) {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "WORKERS must be at least 1"));
+        }
+        let persist = parse_env_var("PERSIST", true)?;
+        let save_retries = parse_env_var("SAVE_RETRIES", 3)?;
+        let backup_keep = parse_env_var("BACKUP_KEEP", 5)?;
+        let unique_names = parse_env_var("UNIQUE_NAMES", false)?;
+        let maintenance = parse_env_var("MAINTENANCE", false)?;
+        let read_only = read_only()?;
+        let request_timeout = match parse_env_var("REQUEST_TIMEOUT_MS", 30_000)? {
+            0 => None,
+            ms => Some(Duration::from_millis(ms)),
+        };
+        let max_items = Some(parse_env_var("MAX_ITEMS", 0)?).filter(|max| *max > 0);
+        let bulk_delete_confirm_ratio = parse_env_var("BULK_DELETE_CONFIRM_RATIO", 0.5)?;
+        if !(0.0..=1.0).contains(&bulk_delete_confirm_ratio) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "BULK_DELETE_CONFIRM_RATIO must be between 0 and 1",
+            ));
+        }
+        let tls = resolve_tls(std::env::var("TLS_CERT").ok(), std::env::var("TLS_KEY").ok())?;
+        if tls.is_some() && !cfg!(feature = "tls") {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "TLS_CERT and TLS_KEY require building with --features tls",
+            ));
+        }
+        let database_url = database_url();
+        if database_url.is_some() {
+            // SQLite 後端只提供基本的 CRUD，不會套用這些只有 JSON 後端支援的設定，啟動失敗而不是默默忽略
+            let normalization = NameNormalization::from_env()?;
+            let json_only = [
+                ("MAX_ITEMS", max_items.is_some()),
+                ("UNIQUE_NAMES", unique_names),
+                ("NORMALIZE_NAMES", normalization.collapse_whitespace),
+                ("LOWERCASE_NAMES", normalization.lowercase),
+                ("BULK_DELETE_CONFIRM_RATIO", std::env::var_os("BULK_DELETE_CONFIRM_RATIO").is_some()),
+                ("DEFAULT_TAGS", !env_list("DEFAULT_TAGS").is_empty()),
+                ("DEFAULT_NAME_PREFIX", std::env::var("DEFAULT_NAME_PREFIX").is_ok_and(|prefix| !prefix.is_empty())),
+                ("AUDIT_LOG", audit_log_path().is_some()),
+            ];
+            if let Some((name, _)) = json_only.iter().find(|(_, set)| *set) {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("{} is not supported with DATABASE_URL", name),
+                ));
+            }
+        }
+        if database_url.is_some() && !cfg!(feature = "sqlite") {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "DATABASE_URL requires building with --features sqlite",
+            ));
+        }
+        Ok(ServerConfig {
+            host,
+            port,
+            max_body_bytes,
+            rate_limit_per_min,
+            idempotency_ttl,
+            workers,
+            persist,
+            tls,
+            save_retries,
+            backup_keep,
+            unique_names,
+            maintenance,
+            read_only,
+            request_timeout,
+            max_items,
+            bulk_delete_confirm_ratio,
+            database_url,
+        })
+    }
+
+    // 組合成 host:port 形式的綁定字串
+    fn bind_address(&self) -> String {
+        format!("{}:{}", self.host, self.port)
+    }
+}
+
+// 從環境變數 API_PREFIX 解析項目服務的路徑前綴，例如 /api/v1，預設為空字串
+// 統一為以 / 開頭且不以 / 結尾的形式
+fn api_prefix() -> String {
+    let prefix = std::env::var("API_PREFIX").unwrap_or_default();
+    let prefix = prefix.trim_matches('/');
+    if prefix.is_empty() {
+        String::new()
+    } else {
+        format!("/{}", prefix)
+    }
+}
+
+// 創建項目時套用的預設值，啟動時從環境變數讀取
+struct ItemDefaults {
+    tags: Vec<String>,           // DEFAULT_TAGS：以逗號分隔，項目沒有標籤時使用
+    name_prefix: Option<String>, // DEFAULT_NAME_PREFIX：項目沒有名稱時以前綴加上 ID 作為名稱
+}
+
+impl ItemDefaults {
+    fn from_env() -> Self {
+        let tags = env_list("DEFAULT_TAGS");
+        let name_prefix = std::env::var("DEFAULT_NAME_PREFIX").ok().filter(|prefix| !prefix.is_empty());
+        ItemDefaults { tags, name_prefix }
+    }
+
+    // 只填入請求中省略或為空的欄位
+    fn apply(&self, item: &mut Item) {
+        if item.tags.is_empty() {
+            item.tags = self.tags.clone();
+        }
+        if let Some(prefix) = self.name_prefix.as_ref().filter(|_| item.name.trim().is_empty()) {
+            item.name = format!("{}{}", prefix, item.id); // 例如 item-5
+        }
+    }
+}
+
+// 寫入項目前整理名稱的設定，避免只有空白或大小寫不同的重複名稱
+struct NameNormalization {
+    collapse_whitespace: bool, // NORMALIZE_NAMES：去除前後空白並將連續的空白合併為一個空格
+    lowercase: bool,           // LOWERCASE_NAMES：將名稱轉為小寫
+}
+
+impl NameNormalization {
+    fn from_env() -> io::Result<Self> {
+        Ok(NameNormalization {
+            collapse_whitespace: parse_env_var("NORMALIZE_NAMES", false)?,
+            lowercase: parse_env_var("LOWERCASE_NAMES", false)?,
+        })
+    }
+
+    // 依設定整理名稱，例如 "  Foo   Bar  " 整理為 "Foo Bar"，啟用小寫時為 "foo bar"
+    fn apply(&self, name: &mut String) {
+        if self.collapse_whitespace {
+            *name = name.split_whitespace().collect::<Vec<_>>().join(" ");
+        }
+        if self.lowercase {
+            *name = name.to_lowercase();
+        }
+    }
+}
+
+// 從環境變數 READ_ONLY 解析是否啟用唯讀模式，預設為 false
+fn read_only() -> io::Result<bool> {
+    parse_env_var("READ_ONLY", false)
+}
+
+// 從環境變數 ITEMS_FILE 解析資料文件路徑，預設為 items.json
+fn items_file_path() -> PathBuf {
+    std::env::var("ITEMS_FILE")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| PathBuf::from("items.json"))
+}
+
+// 從環境變數 AUDIT_LOG 解析稽核日誌路徑，未設定時不記錄
+fn audit_log_path() -> Option<PathBuf> {
+    std::env::var("AUDIT_LOG").ok().filter(|path| !path.is_empty()).map(PathBuf::from)
+}
+
+// 從環境變數 API_KEY 讀取 API 金鑰，設定後所有請求（包含 /admin 下的管理服務）需帶有相符的 X-API-Key 標頭
+fn api_key() -> Option<String> {
+    std::env::var("API_KEY").ok().filter(|key| !key.is_empty())
+}
+
+// 從環境變數 DATABASE_URL 解析 SQLite 資料庫的位址，例如 sqlite:items.db，未設定時使用 JSON 文件
+fn database_url() -> Option<String> {
+    std::env::var("DATABASE_URL").ok().filter(|url| !url.is_empty())
+}
+
+// 從環境變數 SEED_DIR 解析種子資料目錄，預設為 seed
+fn seed_dir_path() -> PathBuf {
+    std::env::var("SEED_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| PathBuf::from("seed"))
+}
+
+// 在資料文件旁的 .lock 文件上取得獨佔鎖，已有其他實例持有時返回錯誤
+// 返回的文件需保持開啟直到伺服器關閉，關閉文件時鎖也會被釋放
+fn lock_data_file(path: &Path) -> io::Result<File> {
+    let mut lock_path = path.as_os_str().to_owned();
+    lock_path.push(".lock");
+    let lock_path = PathBuf::from(lock_path); // 例如 items.json.lock
+
+    let file = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(&lock_path)?;
+    file.try_lock_exclusive().map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::WouldBlock,
+            format!(
+                "{} is locked by another instance ({}): {}",
+                path.display(),
+                lock_path.display(),
+                e
+            ),
+        )
+    })?;
+    Ok(file)
+}
+
+// 資料文件目前的修改時間，文件不存在或無法讀取時返回 None
+fn file_mtime(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
+
+// 負責從 JSON 文件讀取項目並建立 ID 索引，內容無法解析為 Vec<Item> 時返回錯誤而不是丟棄資料
+// 文件是舊版本的格式時轉換後寫回一次，原本的文件保留為 items.json.v<版本>
+fn load_items(path: &Path) -> io::Result<ItemStore> {
+    if !path.exists() {
+        return Ok(ItemStore::default()); // 如果文件不存在，返回空的儲存
+    }
+
+    let mut file = File::open(path)?;
+    let mut contents = Vec::new();
+    file.read_to_end(&mut contents)?;
+    let (items, schema_version) = parse_data_file(&contents).map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("unable to parse {}: {}", path.display(), e),
+        )
+    })?; // 將 JSON 解析為 Vec<Item>
+    if schema_version < SCHEMA_VERSION {
+        let mut original = path.as_os_str().to_owned();
+        original.push(format!(".v{}", schema_version));
+        let original = PathBuf::from(original); // 例如 items.json.v0，不受 BACKUP_KEEP 的輪替影響
+        fs::copy(path, &original)?;
+        save_items(path, &items, 0)?;
+        log::info!(
+            "migrated {} from schema version {} to {}, the original file is kept as {}",
+            path.display(),
+            schema_version,
+            SCHEMA_VERSION,
+            original.display()
+        );
+    }
+    Ok(ItemStore::new(items))
+}
+
+// 種子文件的內容，可以是多個項目的陣列或單一項目
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SeedFile {
+    Many(Vec<NewItem>),
+    One(NewItem),
+}
+
+// 讀取目錄中所有的 *.json 種子文件並合併，依文件名稱順序重新分配 ID
+// 無法讀取或解析的文件只記錄警告並略過，目錄不存在時返回空向量
+fn load_seed(dir: &Path) -> Vec<Item> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return vec![],
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    paths.sort(); // 依文件名稱排序，讓分配的 ID 可重現
+
+    let mut items = Vec::new();
+    for path in paths {
+        let seed = match fs::read_to_string(&path).map_err(|e| e.to_string()).and_then(|contents| {
+            serde_json::from_str::<SeedFile>(&contents).map_err(|e| e.to_string())
+        }) {
+            Ok(seed) => seed,
+            Err(e) => {
+                log::warn!("skipping seed file {}: {}", path.display(), e);
+                continue;
+            }
+        };
+        let new_items = match seed {
+            SeedFile::Many(new_items) => new_items,
+            SeedFile::One(new_item) => vec![new_item],
+        };
+        for new_item in new_items {
+            let item = Item {
+                tags: new_item.tags,
+                ..Item::new(items.len() + 1, new_item.name) // 忽略種子文件中的 ID 及 parent_id，因為 ID 會重新分配
+            };
+            if let Err(e) = validate_item(&item) {
+                log::warn!("skipping invalid item in seed file {}: {}", path.display(), e);
+                continue;
+            }
+            items.push(item);
+        }
+        log::info!("loaded seed file {}", path.display());
+    }
+    items
+}
+
+// 資料文件的內容，schema_version 讓之後的版本可以遷移舊的文件
+#[derive(Serialize)]
+struct DataFile<'a> {
+    schema_version: u64,
+    items: &'a [Item],
+}
+
+// 以資料文件的格式序列化項目，以縮排格式寫入，方便人工檢視及版本控制
+fn serialize_data_file(items: &[Item]) -> serde_json::Result<String> {
+    serde_json::to_string_pretty(&DataFile {
+        schema_version: SCHEMA_VERSION,
+        items,
+    })
+}
+
+// 解析資料文件或匯入文件的內容，舊版本的格式會先轉換為目前的格式，返回項目及文件原本的版本
+fn parse_data_file(contents: &[u8]) -> Result<(Vec<Item>, u64), String> {
+    let value: serde_json::Value = serde_json::from_slice(contents).map_err(|e| e.to_string())?;
+    let (items, schema_version) = migration::migrate(value)?;
+    let items = serde_json::from_value(serde_json::Value::Array(items)).map_err(|e| e.to_string())?;
+    Ok((items, schema_version))
+}
+
+// 負責將項目寫入 JSON 文件
+// 先寫入同目錄下的暫存文件再重新命名覆蓋，確保中途崩潰時原文件保持完整
+fn save_items(path: &Path, items: &[Item], backup_keep: usize) -> io::Result<()> {
+    if backup_keep > 0 && path.exists() { // 文件尚不存在時不需要備份
+        if let Err(e) = backup_items_file(path, backup_keep) {
+            log::warn!("failed to back up {}: {}", path.display(), e); // 備份失敗時仍然寫入新的內容
+        }
+    }
+
+    let mut tmp_path = path.as_os_str().to_owned();
+    tmp_path.push(".tmp");
+    let tmp_path = PathBuf::from(tmp_path); // 例如 items.json.tmp
+
+    let data = serialize_data_file(items)?;
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true) // 每次寫入時先清空暫存文件
+        .open(&tmp_path)?;
+    file.write_all(data.as_bytes())?;
+    file.sync_all()?; // 確保內容已寫入磁碟
+    fs::rename(&tmp_path, path) // 在同一檔案系統上重新命名為原子操作
+}
+
+// 將目前的資料文件複製為 items.json.bak.<時間>，並只保留最近的 keep 個備份
+fn backup_items_file(path: &Path, keep: usize) -> io::Result<()> {
+    let file_name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "invalid data file name"))?;
+    let prefix = format!("{}.bak.", file_name);
+    let timestamp = Utc::now().format("%Y%m%dT%H%M%S%.3fZ"); // 依名稱排序即為時間順序
+    fs::copy(path, path.with_file_name(format!("{}{}", prefix, timestamp)))?;
+
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let mut backups: Vec<PathBuf> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|p| p.file_name().and_then(|n| n.to_str()).is_some_and(|n| n.starts_with(&prefix)))
+        .collect();
+    backups.sort();
+    let excess = backups.len().saturating_sub(keep);
+    for old in &backups[..excess] {
+        fs::remove_file(old)?; // 刪除較舊的備份
+    }
+    Ok(())
+}
+
+// 每次重試前等待的基本時間，之後每次加倍
+const SAVE_RETRY_BACKOFF: Duration = Duration::from_millis(50);
+
+// 執行寫入操作，失敗時最多重試 retries 次，用於應付暫時性的檔案系統錯誤（例如 EBUSY）
+fn with_retries(retries: u32, mut op: impl FnMut() -> io::Result<()>) -> io::Result<()> {
+    let mut attempt = 0;
+    loop {
+        match op() {
+            Ok(()) => return Ok(()),
+            Err(e) if attempt < retries => {
+                let backoff = SAVE_RETRY_BACKOFF * 2u32.pow(attempt);
+                attempt += 1;
+                log::warn!("unable to save items, retrying in {:?} ({}/{}): {}", backoff, attempt, retries, e);
+                std::thread::sleep(backoff);
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+// 項目名稱允許的最大長度（字元數）
+const MAX_NAME_LEN: usize = 256;
+
+// 驗證項目內容，名稱不可為空白且長度不可超過 MAX_NAME_LEN
+fn validate_item(item: &Item) -> Result<(), String> {
+    if item.name.trim().is_empty() {
+        return Err("name must not be empty".to_string());
+    }
+    if item.name.chars().count() > MAX_NAME_LEN {
+        return Err(format!("name must be at most {} characters", MAX_NAME_LEN));
+    }
+    Ok(())
+}
+
+// 項目 ID 對應其 parent_id，已刪除的項目不能作為父項目
+fn parent_links<'a>(items: impl IntoIterator<Item = &'a Item>) -> HashMap<usize, Option<usize>> {
+    items.into_iter().filter(|i| !i.deleted).map(|i| (i.id, i.parent_id)).collect()
+}
+
+// 檢查 parent_id：父項目必須存在，且沿著父項目往上不能回到項目本身
+fn validate_parent(links: &HashMap<usize, Option<usize>>, id: usize, parent_id: Option<usize>) -> Result<(), ApiError> {
+    let Some(parent_id) = parent_id else {
+        return Ok(());
+    };
+    if parent_id != id && !links.contains_key(&parent_id) {
+        return Err(ApiError::Validation(format!("parent item {} not found", parent_id)));
+    }
+    let mut visited = HashSet::new(); // 避免文件中已存在的循環造成無限迴圈
+    let mut current = Some(parent_id);
+    while let Some(ancestor) = current.filter(|a| visited.insert(*a)) {
+        if ancestor == id {
+            return Err(ApiError::Validation(format!("parent_id {} would create a cycle", parent_id)));
+        }
+        current = links.get(&ancestor).copied().flatten();
+    }
+    Ok(())
+}
+
+// 伺服器關閉前最後一次將記憶體中的項目寫入 JSON 文件
+fn flush_items(state: &AppState) -> io::Result<()> {
+    let items = state
+        .items
+        .read()
+        .map_err(|_| io::Error::other("item store is unavailable"))?;
+    log::info!("shutting down, flushing {} items", items.len());
+    state.save(&items)
+}
+
+// 背景任務：每秒最多將變更寫入 JSON 文件一次，批次處理高頻率的修改
+// 相同的錯誤（例如文件被外部修改）只記錄一次，恢復後再記錄一次
+fn spawn_flush_task(state: web::Data<AppState>) {
+    actix_web::rt::spawn(async move {
+        let mut interval = actix_web::rt::time::interval(Duration::from_secs(1));
+        let mut last_error: Option<String> = None;
+        loop {
+            interval.tick().await;
+            let state = state.clone();
+            let result = match web::block(move || state.flush_if_dirty()).await { // 在阻塞執行緒池中寫入，不佔用 worker
+                Ok(result) => result.map_err(|e| e.to_string()),
+                Err(e) => Err(e.to_string()),
+            };
+            match result {
+                Err(e) if last_error.as_ref() != Some(&e) => {
+                    log::error!("unable to save items: {}", e);
+                    last_error = Some(e);
+                }
+                Ok(()) if last_error.take().is_some() => log::info!("saving items succeeded again"),
+                _ => {}
+            }
+        }
+    });
+}
+
+// 根據項目內容計算 ETag，與 /items/checksum 相同使用 SHA-256，不受 Rust 版本或執行環境影響
+fn item_etag(item: &Item) -> String {
+    let serialized = serde_json::to_vec(item).unwrap_or_default(); // Item 只包含可序列化的欄位
+    format!("\"{:x}\"", Sha256::digest(serialized))
+}
+
+// 檢查 If-Match 標頭：未提供、為 * 或包含目前的 ETag 時才允許修改
+fn if_match_satisfied(req: &HttpRequest, item: &Item) -> bool {
+    let Some(value) = req.headers().get(header::IF_MATCH) else {
+        return true;
+    };
+    let Ok(value) = value.to_str() else {
+        return false;
+    };
+    let etag = item_etag(item);
+    value
+        .split(',')
+        .map(str::trim)
+        .any(|tag| tag == "*" || tag == etag)
+}
+
+// 名稱是否已被 ID 不同的其他項目使用
+fn name_taken(items: &[Item], name: &str, id: usize) -> bool {
+    items.iter().any(|i| i.name == name && i.id != id)
+}
+
+// 啟用 UNIQUE_NAMES 時名稱重複的錯誤
+fn duplicate_name(name: &str) -> ApiError {
+    ApiError::Conflict(format!("item with name {:?} already exists", name))
+}
+
+// 客戶端是否在 Accept 標頭中要求 YAML 格式
+#[cfg(feature = "yaml")]
+fn wants_yaml(req: &HttpRequest) -> bool {
+    let Some(accept) = req.headers().get(header::ACCEPT).and_then(|v| v.to_str().ok()) else {
+        return false;
+    };
+    accept.split(',').any(|media_type| {
+        let media_type = media_type.split(';').next().unwrap_or_default().trim(); // 忽略 q 等參數
+        media_type.eq_ignore_ascii_case("application/yaml") || media_type.eq_ignore_ascii_case("text/yaml")
+    })
+}
+
+// 依 Accept 標頭以 JSON 或 YAML 返回響應內容，預設為 JSON
+fn respond_negotiated<T: Serialize>(
+    req: &HttpRequest,
+    response: &mut HttpResponseBuilder,
+    value: &T,
+) -> Result<HttpResponse, ApiError> {
+    #[cfg(feature = "yaml")]
+    if wants_yaml(req) {
+        let body = serde_yaml::to_string(value)
+            .map_err(|e| ApiError::Internal(format!("unable to serialize response: {}", e)))?;
+        return Ok(response.content_type("application/yaml").body(body));
+    }
+    #[cfg(not(feature = "yaml"))]
+    let _ = req; // 未啟用 yaml feature 時一律返回 JSON
+    Ok(response.json(value))
+}
+
+// 限制 JSON 請求內容的大小，並將解析錯誤轉換為統一格式的響應
+// 超過大小限制時返回 413，其他解析錯誤返回 400
+fn json_config(limit: usize) -> web::JsonConfig {
+    web::JsonConfig::default().limit(limit).error_handler(|err, _req| {
+        let response = match &err {
+            JsonPayloadError::Overflow { .. } | JsonPayloadError::OverflowKnownLength { .. } => {
+                HttpResponse::PayloadTooLarge().json(ErrorResponse {
+                    detail: Some(err.to_string()),
+                    ..ErrorResponse::new("payload_too_large", "payload too large")
+                })
+            }
+            _ => HttpResponse::BadRequest().json(ErrorResponse {
+                detail: Some(err.to_string()),
+                ..ErrorResponse::new("invalid_json", "invalid json")
+            }),
+        };
+        actix_web::error::InternalError::from_response(err, response).into()
+    })
+}
+
+// 路徑參數無法解析時返回 400 及統一格式的錯誤，而不是 actix-web 預設的 404
+// 目前所有路徑參數都是項目 ID，例如 /items/abc
+fn path_config() -> web::PathConfig {
+    web::PathConfig::default().error_handler(|err, _req| {
+        let response = HttpResponse::BadRequest().json(ErrorResponse {
+            detail: Some(err.to_string()),
+            ..ErrorResponse::new("invalid_id", "id must be a non-negative integer")
+        });
+        actix_web::error::InternalError::from_response(err, response).into()
+    })
+}
+
+/// 創建新項目（POST 請求）
+#[utoipa::path(
+    post,
+    path = "/items",
+    request_body = NewItem,
+    params(
+        ("Idempotency-Key" = Option<String>, Header, description = "Retries with the same key return the originally created item instead of creating another")
+    ),
+    responses(
+        (status = 201, description = "Created new item successfully, or returned the item already created with the same Idempotency-Key", body = Item,
+            headers(("Location" = String, description = "URL of the created item, e.g. /items/1"))),
+        (status = 400, description = "Invalid item name, missing parent item, parent_id cycle or malformed JSON body", body = ErrorResponse),
+        (status = 413, description = "Request body exceeds MAX_BODY_BYTES", body = ErrorResponse),
+        (status = 409, description = "Item with the same id already exists, or with the same name when UNIQUE_NAMES is enabled", body = ErrorResponse),
+        (status = 507, description = "Creating the item would exceed MAX_ITEMS", body = ErrorResponse),
+        (status = 503, description = "Maintenance mode is enabled; retry after the Retry-After delay", body = ErrorResponse),
+        (status = 500, description = "Internal Server Error", body = ErrorResponse)
+    )
+)]
+#[post("/items")]
+async fn create_item(req: HttpRequest, item: web::Json<NewItem>, data: web::Data<AppState>) -> Result<HttpResponse, ApiError> {
+    data.metrics.record(Operation::Create); // 記錄請求次數
+    let item = item.into_inner();
+    let idempotency_key = req
+        .headers()
+        .get("Idempotency-Key")
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
+    let mut items = data.write_items()?; // 獲取寫入鎖
+    let mut idempotency_keys = data // 在寫入鎖之後獲取，避免死結
+        .idempotency_keys
+        .lock()
+        .map_err(|_| ApiError::Internal("item store is unavailable".to_string()))?;
+
+    let ttl = data.idempotency_ttl;
+    idempotency_keys.retain(|_, (_, created)| created.elapsed() < ttl); // 清除過期的 Idempotency-Key
+    if let Some((id, _)) = idempotency_key.as_ref().and_then(|key| idempotency_keys.get(key)) {
+        if let Some(existing) = items.get(*id) { // 重試的請求，返回原本創建的項目
+            return Ok(HttpResponse::Created()
+                .insert_header((header::LOCATION, format!("{}/items/{}", data.api_prefix, existing.id)))
+                .json(existing.clone()));
+        }
+    }
+
+    data.check_capacity(&items, 1)?; // 超過項目數量上限時返回 507 Insufficient Storage 響應
+    let id = match item.id {
+        Some(id) if items.get(id).is_some() => { // 檢查 ID 是否已被使用
+            return Err(ApiError::Conflict(format!("item with id {} already exists", id))); // 返回 409 Conflict 響應
+        }
+        Some(id) => id,
+        None => items.iter().map(|i| i.id).max().unwrap_or(0) + 1, // 分配下一個可用的 ID
+    };
+    let mut new_item = Item {
+        tags: item.tags,
+        parent_id: item.parent_id,
+        ..Item::new(id, item.name)
+    };
+    data.defaults.apply(&mut new_item); // 套用預設標籤及名稱
+    data.name_normalization.apply(&mut new_item.name); // 整理名稱中的空白及大小寫
+    validate_item(&new_item).map_err(ApiError::Validation)?; // 驗證項目內容
+    validate_parent(&parent_links(items.iter()), id, new_item.parent_id)?; // 檢查父項目是否存在
+    if data.unique_names && name_taken(&items, &new_item.name, id) { // 檢查名稱是否已被使用
+        return Err(duplicate_name(&new_item.name)); // 返回 409 Conflict 響應
+    }
+    new_item.version = data.next_version(); // 所有檢查通過後才分配版本號，被拒絕的請求不會消耗版本號
+    items.push(new_item.clone()); // 將新項目添加到 Vec 中
+    if let Some(key) = idempotency_key {
+        idempotency_keys.insert(key, (new_item.id, Instant::now())); // 記錄 Idempotency-Key 以便辨識重試
+    }
+    data.mark_dirty(); // 標記需要寫入，由背景任務寫入 JSON 文件
+    data.publish(ItemEventKind::Created, new_item.id); // 通知訂閱者
+    data.audit("POST", new_item.id, None, Some(&new_item.name)); // 記錄到稽核日誌
+    Ok(HttpResponse::Created()
+        .insert_header((header::LOCATION, format!("{}/items/{}", data.api_prefix, new_item.id))) // 指向新項目的位置
+        .json(new_item)) // 返回 201 Created 響應及新項目
+}
+
+/// 批次創建項目（POST 請求）
+#[utoipa::path(
+    post,
+    path = "/items/bulk",
+    request_body = [NewItem],
+    responses(
+        (status = 201, description = "Created all items successfully", body = [Item]),
+        (status = 400, description = "Invalid item name, missing parent item, parent_id cycle or malformed JSON body", body = ErrorResponse),
+        (status = 413, description = "Request body exceeds MAX_BODY_BYTES", body = ErrorResponse),
+        (status = 409, description = "An item with the same id already exists, or with the same name when UNIQUE_NAMES is enabled", body = ErrorResponse),
+        (status = 507, description = "Creating all items would exceed MAX_ITEMS; none are created", body = ErrorResponse),
+        (status = 503, description = "Maintenance mode is enabled; retry after the Retry-After delay", body = ErrorResponse),
+        (status = 500, description = "Internal Server Error", body = ErrorResponse)
+    )
+)]
+#[post("/items/bulk")]
+async fn create_items_bulk(new_items: web::Json<Vec<NewItem>>, data: web::Data<AppState>) -> Result<HttpResponse, ApiError> {
+    data.metrics.record(Operation::Create); // 記錄請求次數
+    let new_items = new_items.into_inner();
+    let mut items = data.write_items()?; // 獲取寫入鎖
+    data.check_capacity(&items, new_items.len())?; // 整批超過項目數量上限時全部不創建，返回 507 Insufficient Storage 響應
+
+    let mut used_ids: HashSet<usize> = items.iter().map(|i| i.id).collect();
+    for id in new_items.iter().filter_map(|i| i.id) {
+        if !used_ids.insert(id) { // 檢查 ID 是否與現有或同批次項目重複
+            return Err(ApiError::Conflict(format!("item with id {} already exists", id))); // 返回 409 Conflict 響應
+        }
+    }
+
+    let mut next_id = used_ids.iter().max().copied().unwrap_or(0) + 1; // 下一個可分配的 ID
+    let mut created = Vec::with_capacity(new_items.len());
+    for new_item in new_items {
+        let id = match new_item.id {
+            Some(id) => id,
+            None => { // 未指定 ID 時依序分配
+                next_id += 1;
+                next_id - 1
+            }
+        };
+        let mut item = Item {
+            tags: new_item.tags,
+            parent_id: new_item.parent_id,
+            ..Item::new(id, new_item.name)
+        };
+        data.defaults.apply(&mut item); // 套用預設標籤及名稱
+        data.name_normalization.apply(&mut item.name); // 整理名稱中的空白及大小寫
+        validate_item(&item).map_err(ApiError::Validation)?; // 驗證項目內容，任何一個無效則全部不創建
+        if data.unique_names && (name_taken(&items, &item.name, id) || name_taken(&created, &item.name, id)) {
+            return Err(duplicate_name(&item.name)); // 名稱與現有或同批次項目重複
+        }
+        created.push(item);
+    }
+    let links = parent_links(items.iter().chain(&created)); // 父項目可以是現有項目或同批次的項目
+    for item in &created {
+        validate_parent(&links, item.id, item.parent_id)?;
+    }
+    for item in &mut created {
+        item.version = data.next_version(); // 整批通過檢查後才分配版本號
+    }
+
+    items.extend(created.iter().cloned()); // 將所有新項目添加到 Vec 中
+    data.mark_dirty(); // 標記需要寫入，由背景任務寫入 JSON 文件
+    for item in &created {
+        data.publish(ItemEventKind::Created, item.id); // 通知訂閱者
+        data.audit("POST", item.id, None, Some(&item.name)); // 記錄到稽核日誌
+    }
+    Ok(HttpResponse::Created().json(created)) // 返回 201 Created 響應及新項目
+}
+
+/// 依前綴批次重新命名項目（POST 請求）
+#[utoipa::path(
+    post,
+    path = "/items/bulk-rename",
+    params(DryRunQuery),
+    request_body = BulkRename,
+    responses(
+        (status = 200, description = "Renamed all matching items; count is the number of items changed. With dry_run=true returns a DryRunResult instead", body = ItemCount),
+        (status = 400, description = "Empty prefix, invalid resulting name or malformed JSON body", body = ErrorResponse),
+        (status = 409, description = "A resulting name is used by another item when UNIQUE_NAMES is enabled", body = ErrorResponse),
+        (status = 413, description = "Request body exceeds MAX_BODY_BYTES", body = ErrorResponse),
+        (status = 503, description = "Maintenance mode is enabled; retry after the Retry-After delay", body = ErrorResponse),
+        (status = 500, description = "Internal Server Error", body = ErrorResponse)
+    )
+)]
+#[post("/items/bulk-rename")]
+async fn bulk_rename_items(
+    query: web::Query<DryRunQuery>,
+    rename: web::Json<BulkRename>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError> {
+    data.metrics.record(Operation::Update); // 記錄請求次數
+    if rename.prefix.is_empty() {
+        return Err(ApiError::Validation("prefix must not be empty".to_string()));
+    }
+    let mut items = data.write_items()?; // 獲取寫入鎖，整個操作期間獨佔資料
+
+    let mut renamed = Vec::new(); // 先計算所有新名稱，驗證通過後才一次套用
+    for (index, item) in items.iter().enumerate().filter(|(_, i)| !i.deleted) {
+        if let Some(rest) = item.name.strip_prefix(&rename.prefix) {
+            let mut updated = item.clone();
+            updated.name = format!("{}{}", rename.replacement, rest);
+            data.name_normalization.apply(&mut updated.name); // 與創建及更新相同，整理名稱中的空白及大小寫
+            validate_item(&updated).map_err(ApiError::Validation)?; // 任何一個無效則全部不修改
+            renamed.push((index, updated));
+        }
+    }
+    if data.unique_names {
+        // 重新命名後的名稱不可與未修改的項目或同批次的其他項目重複
+        let mut names: HashSet<&str> = items
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| !renamed.iter().any(|(i, _)| i == index))
+            .map(|(_, item)| item.name.as_str())
+            .collect();
+        if let Some((_, item)) = renamed.iter().find(|(_, item)| !names.insert(item.name.as_str())) {
+            return Err(duplicate_name(&item.name)); // 返回 409 Conflict 響應
+        }
+    }
+
+    if query.dry_run.unwrap_or(false) { // 只預覽，不修改項目
+        let items: Vec<Item> = renamed.into_iter().map(|(_, item)| item).collect();
+        return Ok(HttpResponse::Ok().json(DryRunResult {
+            dry_run: true,
+            count: items.len(),
+            items,
+        }));
+    }
+
+    let now = Local::now();
+    for (index, updated) in &renamed {
+        data.audit("POST", updated.id, Some(&items[*index].name), Some(&updated.name)); // 記錄到稽核日誌
+        items[*index].name = updated.name.clone();
+        items[*index].updated_at = now;
+        items[*index].version = data.next_version(); // 以全域變更計數器更新版本號
+        data.publish(ItemEventKind::Updated, items[*index].id); // 通知訂閱者
+    }
+    if !renamed.is_empty() {
+        data.mark_dirty(); // 標記需要寫入，所有修改只寫入文件一次
+    }
+    Ok(HttpResponse::Ok().json(ItemCount { count: renamed.len() })) // 返回修改的項目數量
+}
+
+/// 批次軟刪除項目（POST 請求）
+#[utoipa::path(
+    post,
+    path = "/items/bulk-delete",
+    params(BulkDeleteQuery),
+    request_body = BulkDelete,
+    responses(
+        (status = 200, description = "Marked the listed items as deleted; count is the number of items deleted, unknown ids are ignored. With dry_run=true returns a DryRunResult instead", body = ItemCount),
+        (status = 400, description = "Malformed JSON body, or the request would delete more than BULK_DELETE_CONFIRM_RATIO of the items without confirm=true", body = ErrorResponse),
+        (status = 413, description = "Request body exceeds MAX_BODY_BYTES", body = ErrorResponse),
+        (status = 503, description = "Maintenance mode is enabled; retry after the Retry-After delay", body = ErrorResponse),
+        (status = 500, description = "Internal Server Error", body = ErrorResponse)
+    )
+)]
+#[post("/items/bulk-delete")]
+async fn bulk_delete_items(
+    query: web::Query<BulkDeleteQuery>,
+    request: web::Json<BulkDelete>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError> {
+    data.metrics.record(Operation::Delete); // 記錄請求次數
+    let ids: HashSet<usize> = request.into_inner().ids.into_iter().collect();
+    let mut items = data.write_items()?; // 獲取寫入鎖
+
+    if query.dry_run.unwrap_or(false) { // 只預覽，不修改項目
+        let matched: Vec<Item> = items
+            .iter()
+            .filter(|i| !i.deleted && ids.contains(&i.id))
+            .cloned()
+            .collect();
+        return Ok(HttpResponse::Ok().json(DryRunResult {
+            dry_run: true,
+            count: matched.len(),
+            items: matched,
+        }));
+    }
+
+    let matched = items.iter().filter(|i| !i.deleted && ids.contains(&i.id)).count();
+    data.confirm_delete(&items, matched, query.confirm)?; // 一次刪除太多項目時要求確認
+
+    let now = Local::now();
+    let mut count = 0;
+    for item in items.iter_mut().filter(|i| !i.deleted && ids.contains(&i.id)) {
+        item.deleted = true; // 與單一刪除相同，只標記為已刪除
+        item.updated_at = now;
+        item.version = data.next_version();
+        data.publish(ItemEventKind::Deleted, item.id); // 通知訂閱者
+        data.audit("POST", item.id, Some(&item.name), None); // 記錄到稽核日誌
+        count += 1;
+    }
+    if count > 0 {
+        data.mark_dirty(); // 標記需要寫入，所有刪除只寫入文件一次
+    }
+    Ok(HttpResponse::Ok().json(ItemCount { count })) // 返回刪除的項目數量
+}
+
+// 從 multipart 請求中讀取名為 file 的欄位內容
+async fn read_import_file(mut payload: Multipart) -> Result<Vec<u8>, ApiError> {
+    let invalid = |e: actix_multipart::MultipartError| ApiError::Validation(e.to_string());
+    while let Some(mut field) = payload.try_next().await.map_err(invalid)? {
+        if field.name() != Some("file") {
+            continue; // 略過其他欄位
+        }
+        let mut contents = Vec::new();
+        while let Some(chunk) = field.try_next().await.map_err(invalid)? {
+            if contents.len() + chunk.len() > MAX_IMPORT_BYTES {
+                return Err(ApiError::PayloadTooLarge(format!("file must be at most {} bytes", MAX_IMPORT_BYTES)));
+            }
+            contents.extend_from_slice(&chunk);
+        }
+        return Ok(contents);
+    }
+    Err(ApiError::Validation("missing file field".to_string()))
+}
+
+/// 從上傳的 JSON 文件匯入項目（POST 請求）
+#[utoipa::path(
+    post,
+    path = "/items/import",
+    params(ImportQuery),
+    request_body(content = Vec<u8>, content_type = "multipart/form-data",
+        description = "A multipart form with a `file` field containing a JSON array of items"),
+    responses(
+        (status = 200, description = "Imported all items; count is the number of items in the file", body = ItemCount),
+        (status = 400, description = "Missing file, unparsable JSON, invalid item, duplicate id, missing parent item, parent_id cycle, unknown mode, or mode=replace would delete more than BULK_DELETE_CONFIRM_RATIO of the items without confirm=true", body = ErrorResponse),
+        (status = 409, description = "UNIQUE_NAMES is enabled and a name is used by another item in the file or in storage; nothing is imported", body = ErrorResponse),
+        (status = 413, description = "The uploaded file is larger than 10 MB", body = ErrorResponse),
+        (status = 503, description = "Maintenance mode is enabled; retry after the Retry-After delay", body = ErrorResponse),
+        (status = 507, description = "The non-deleted items after the import would exceed MAX_ITEMS; nothing is imported", body = ErrorResponse),
+        (status = 500, description = "Internal Server Error", body = ErrorResponse)
+    )
+)]
+#[post("/items/import")]
+async fn import_items(query: web::Query<ImportQuery>, payload: Multipart, data: web::Data<AppState>) -> Result<HttpResponse, ApiError> {
+    data.metrics.record(Operation::Create); // 記錄請求次數
+    let replace = match query.mode.as_deref().unwrap_or("merge") {
+        "replace" => true,
+        "merge" => false,
+        other => return Err(ApiError::Validation(format!("unknown mode value: {}", other))),
+    };
+    let contents = read_import_file(payload).await?; // 讀取上傳的文件
+    let (mut imported, _) = parse_data_file(&contents) // 接受資料文件或項目陣列，舊版本的格式會先轉換
+        .map_err(|e| ApiError::Validation(format!("unable to parse file: {}", e)))?;
+    let mut ids = HashSet::new();
+    for item in &mut imported {
+        data.name_normalization.apply(&mut item.name); // 整理名稱中的空白及大小寫
+        validate_item(item).map_err(ApiError::Validation)?; // 任何一個無效則全部不匯入
+        if !ids.insert(item.id) {
+            return Err(ApiError::Validation(format!("duplicate id {} in file", item.id)));
+        }
+    }
+
+    let mut items = data.write_items()?; // 獲取寫入鎖
+    // 以合併後的項目檢查父項目，父項目可以是文件中的其他項目，取代模式下文件中沒有的項目不能作為父項目
+    let mut links = parent_links(items.iter().filter(|i| !replace || ids.contains(&i.id)));
+    for item in &imported {
+        if item.deleted {
+            links.remove(&item.id);
+        } else {
+            links.insert(item.id, item.parent_id);
+        }
+    }
+    for item in imported.iter().filter(|i| !i.deleted) {
+        validate_parent(&links, item.id, item.parent_id)?; // 任何一個無效則全部不匯入
+    }
+    if data.unique_names {
+        // 名稱不能與文件中的其他項目或文件中沒有的現有項目重複，任何一個重複則全部不匯入
+        for item in &imported {
+            if name_taken(&imported, &item.name, item.id) || items.iter().any(|i| !ids.contains(&i.id) && i.name == item.name) {
+                return Err(duplicate_name(&item.name)); // 返回 409 Conflict 響應
+            }
+        }
+    }
+    // 以匯入後未刪除的項目數量檢查上限，超過上限時全部不匯入
+    let kept = if replace { 0 } else { items.iter().filter(|i| !i.deleted && !ids.contains(&i.id)).count() };
+    let active = items.iter().filter(|i| !i.deleted).count();
+    let adding = (kept + imported.iter().filter(|i| !i.deleted).count()).saturating_sub(active);
+    data.check_capacity(&items, adding)?;
+    if replace {
+        let removing = items.iter().filter(|i| !i.deleted && !ids.contains(&i.id)).count();
+        data.confirm_delete(&items, removing, query.confirm)?; // 與批次刪除相同，刪除太多項目時要求確認
+    }
+    if replace {
+        let now = Local::now();
+        for item in items.iter_mut().filter(|i| !i.deleted && !ids.contains(&i.id)) {
+            item.deleted = true; // 取代模式下軟刪除文件中沒有的項目，之後可以還原
+            item.updated_at = now;
+            item.version = data.next_version(); // 讓增量同步的客戶端得知項目已刪除
+            data.publish(ItemEventKind::Deleted, item.id); // 通知訂閱者
+            data.audit("POST", item.id, Some(&item.name), None); // 記錄到稽核日誌
+        }
+    }
+    let count = imported.len();
+    for mut item in imported {
+        item.version = data.next_version(); // 匯入的項目視為新的修改
+        match items.get_mut(item.id) {
+            Some(existing) => {
+                data.publish(ItemEventKind::Updated, item.id); // 通知訂閱者
+                data.audit("POST", item.id, Some(&existing.name), Some(&item.name)); // 記錄到稽核日誌
+                *existing = item; // 相同 ID 的項目以文件內容為準
+            }
+            None => {
+                data.publish(ItemEventKind::Created, item.id); // 通知訂閱者
+                data.audit("POST", item.id, None, Some(&item.name)); // 記錄到稽核日誌
+                items.push(item);
+            }
+        }
+    }
+    data.mark_dirty(); // 標記需要寫入，所有修改只寫入文件一次
+    Ok(HttpResponse::Ok().json(ItemCount { count })) // 返回匯入的項目數量
+}
+
+// 解析 NDJSON 的一行，空白行直接略過，無法解析時記錄行號
+fn collect_ndjson_line(line: &[u8], line_no: usize, parsed: &mut Vec<(usize, NewItem)>, failed_lines: &mut Vec<usize>) {
+    let line = line.trim_ascii(); // 同時去除 \r\n 換行
+    if line.is_empty() {
+        return;
+    }
+    match serde_json::from_slice::<NewItem>(line) {
+        Ok(new_item) => parsed.push((line_no, new_item)),
+        Err(_) => failed_lines.push(line_no),
+    }
+}
+
+// 在寫入鎖下套用一批 NDJSON 的項目，無效的行記錄到 failed_lines，返回成功匯入的數量
+fn apply_ndjson_batch(data: &AppState, batch: Vec<(usize, NewItem)>, failed_lines: &mut Vec<usize>) -> Result<usize, ApiError> {
+    let mut items = data.write_items()?; // 只在套用時持有寫入鎖，避免緩慢的上傳阻擋其他請求
+    let mut used_ids: HashSet<usize> = items.iter().map(|i| i.id).collect();
+    let mut links = parent_links(items.iter());
+    let mut next_id = used_ids.iter().max().copied().unwrap_or(0) + 1; // 下一個可分配的 ID
+    let mut imported = 0;
+    for (line_no, new_item) in batch {
+        let id = new_item.id.unwrap_or(next_id);
+        let mut item = Item {
+            tags: new_item.tags,
+            parent_id: new_item.parent_id,
+            ..Item::new(id, new_item.name)
+        };
+        data.defaults.apply(&mut item); // 與 POST /items 相同，套用預設標籤及名稱
+        data.name_normalization.apply(&mut item.name); // 整理名稱中的空白及大小寫
+        let valid = !used_ids.contains(&id)
+            && data.check_capacity(&items, 1).is_ok() // 達到項目數量上限後其餘的行都會被略過
+            && validate_item(&item).is_ok()
+            && validate_parent(&links, id, item.parent_id).is_ok() // 父項目可以是同一次匯入中較早的行
+            && !(data.unique_names && name_taken(&items, &item.name, id));
+        if !valid {
+            failed_lines.push(line_no);
+            continue;
+        }
+        item.version = data.next_version(); // 只有成功匯入的項目才遞增版本號
+        used_ids.insert(id);
+        links.insert(id, item.parent_id);
+        next_id = next_id.max(id + 1);
+        data.audit("POST", id, None, Some(&item.name)); // 記錄到稽核日誌
+        items.push(item);
+        imported += 1;
+        data.publish(ItemEventKind::Created, id); // 通知訂閱者
+    }
+    if imported > 0 {
+        data.mark_dirty(); // 標記需要寫入，同一批的新項目只寫入文件一次
+    }
+    Ok(imported)
+}
+
+/// 以 NDJSON 串流匯入項目（POST 請求）
+#[utoipa::path(
+    post,
+    path = "/items/import-ndjson",
+    request_body(content = NewItem, content_type = "application/x-ndjson",
+        description = "One NewItem JSON object per line; ids are assigned when omitted"),
+    responses(
+        (status = 200, description = "Appended every valid line; lines that fail to parse, are invalid, reuse an existing id, reference a missing parent or would exceed MAX_ITEMS are skipped and reported", body = NdjsonImportResult),
+        (status = 400, description = "The request body could not be read; lines are applied in batches of 1000, so earlier batches stay imported", body = ErrorResponse),
+        (status = 413, description = "A single line is larger than 64 KB; earlier batches of 1000 lines stay imported", body = ErrorResponse),
+        (status = 503, description = "Maintenance mode is enabled; retry after the Retry-After delay", body = ErrorResponse),
+        (status = 500, description = "Internal Server Error", body = ErrorResponse)
+    )
+)]
+#[post("/items/import-ndjson")]
+async fn import_items_ndjson(mut payload: web::Payload, data: web::Data<AppState>) -> Result<HttpResponse, ApiError> {
+    data.metrics.record(Operation::Create); // 記錄請求次數
+    let mut parsed = Vec::new(); // 行號及解析後、尚未套用的項目
+    let mut failed_lines = Vec::new();
+    let mut imported = 0;
+    let mut pending = Vec::new(); // 尚未遇到換行的內容
+    let mut line_no = 0;
+    while let Some(chunk) = payload
+        .try_next()
+        .await
+        .map_err(|e| ApiError::Validation(format!("unable to read request body: {}", e)))?
+    {
+        pending.extend_from_slice(&chunk);
+        while let Some(end) = pending.iter().position(|b| *b == b'\n') { // 逐行解析，不需保留整個請求內容
+            line_no += 1;
+            let line: Vec<u8> = pending.drain(..=end).collect();
+            collect_ndjson_line(&line, line_no, &mut parsed, &mut failed_lines);
+            if parsed.len() >= NDJSON_BATCH_LINES {
+                imported += apply_ndjson_batch(&data, std::mem::take(&mut parsed), &mut failed_lines)?;
+            }
+        }
+        if pending.len() > MAX_NDJSON_LINE_BYTES {
+            return Err(ApiError::PayloadTooLarge(format!(
+                "line {} must be at most {} bytes",
+                line_no + 1,
+                MAX_NDJSON_LINE_BYTES
+            )));
+        }
+    }
+    if !pending.is_empty() { // 最後一行可以沒有換行
+        collect_ndjson_line(&pending, line_no + 1, &mut parsed, &mut failed_lines);
+    }
+
+    imported += apply_ndjson_batch(&data, parsed, &mut failed_lines)?;
+    failed_lines.sort_unstable();
+    Ok(HttpResponse::Ok().json(NdjsonImportResult { imported, failed_lines }))
+}
+
+/// 獲取所有項目（GET 及 HEAD 請求，HEAD 只返回狀態碼及標頭）
+#[utoipa::path(
+    get,
+    path = "/items",
+    params(ListQuery),
+    responses(
+        (status = 200, description = "Retrieved all items successfully. With paged=true returns an ItemPage instead. Returned as YAML when Accept is application/yaml or text/yaml and the yaml feature is enabled", body = [Item],
+            headers(("X-Total-Count" = usize, description = "Total number of items before pagination"))),
+        (status = 304, description = "Items have not changed since If-Modified-Since"),
+        (status = 400, description = "Invalid sort, fields or date range parameters", body = ErrorResponse),
+        (status = 500, description = "Internal Server Error", body = ErrorResponse)
+    )
+)]
+#[route("/items", method = "GET", method = "HEAD")]
+async fn get_items(req: HttpRequest, query: web::Query<ListQuery>, data: web::Data<AppState>) -> Result<HttpResponse, ApiError> {
+    data.metrics.record(Operation::Read); // 記錄請求次數
+    let offset = query.offset.unwrap_or(0); // 預設從第一個項目開始
+    let limit = query.limit.unwrap_or(50); // 預設每頁 50 個項目
+    let (sort_field, descending) = parse_sort(&query).map_err(ApiError::Validation)?; // 解析排序參數
+    let fields = query.fields.as_deref().map(parse_fields).transpose().map_err(ApiError::Validation)?; // 解析欄位選取參數
+    let (from, to) = parse_date_range(&query).map_err(ApiError::Validation)?; // 解析創建時間範圍
+    let items = data.read_items()?; // 獲取讀取鎖
+    let last_modified = data.last_modified(); // 持有讀取鎖時讀取，確保與項目內容一致
+    if let Some(header::IfModifiedSince(since)) = req.get_header::<header::IfModifiedSince>() {
+        if last_modified <= SystemTime::from(since) { // 自客戶端的時間後沒有變更
+            return Ok(HttpResponse::NotModified()
+                .insert_header(header::LastModified(last_modified.into()))
+                .finish());
+        }
+    }
+
+    let include_deleted = query.include_deleted.unwrap_or(false);
+    let mut sorted: Vec<&Item> = items
+        .iter()
+        .filter(|i| include_deleted || !i.deleted) // 預設隱藏已軟刪除的項目
+        .filter(|i| query.name.as_ref().is_none_or(|name| &i.name == name)) // 依名稱精確篩選
+        .filter(|i| query.tag.as_ref().is_none_or(|tag| i.tags.contains(tag))) // 依標籤篩選
+        .filter(|i| from.is_none_or(|from| i.created_at >= from) && to.is_none_or(|to| i.created_at <= to)) // 依創建時間篩選
+        .collect();
+    let total = sorted.len(); // 分頁前的項目總數
+
+    match sort_field {
+        SortField::Position => {} // 保持列表中的順序
+        SortField::Id => sorted.sort_by_key(|i| i.id),
+        SortField::Name => sorted.sort_by(|a, b| a.name.cmp(&b.name)),
+    }
+    if descending {
+        sorted.reverse();
+    }
+
+    let page = sorted.into_iter().skip(offset).take(limit); // 擷取分頁範圍內的項目
+    let mut response = HttpResponse::Ok();
+    response
+        .insert_header(("X-Total-Count", total)) // 在標頭中提供項目總數
+        .insert_header(header::LastModified(last_modified.into())); // 提供最後變更時間供條件請求使用
+    let paged = query.paged.unwrap_or(false);
+    match fields {
+        Some(fields) => {
+            let page: Vec<_> = page.map(|i| select_fields(i, &fields)).collect(); // 只返回指定的欄位
+            if paged {
+                respond_negotiated(&req, &mut response, &PagedItems::new(page, total, limit, offset))
+            } else {
+                respond_negotiated(&req, &mut response, &page)
+            }
+        }
+        None => {
+            let page: Vec<Item> = page.cloned().collect(); // 返回分頁後的項目
+            if paged {
+                respond_negotiated(&req, &mut response, &PagedItems::new(page, total, limit, offset))
+            } else {
+                respond_negotiated(&req, &mut response, &page)
+            }
+        }
+    }
+}
+
+/// 依名稱搜尋項目（GET 請求）
+#[utoipa::path(
+    get,
+    path = "/items/search",
+    params(SearchQuery),
+    responses(
+        (status = 200, description = "Retrieved matching items successfully", body = [Item]),
+        (status = 500, description = "Internal Server Error", body = ErrorResponse)
+    )
+)]
+#[get("/items/search")]
+async fn search_items(query: web::Query<SearchQuery>, data: web::Data<AppState>) -> Result<HttpResponse, ApiError> {
+    data.metrics.record(Operation::Read); // 記錄請求次數
+    let q = query.q.to_lowercase(); // 轉為小寫以進行不分大小寫的比對
+    let items = data.read_items()?; // 獲取讀取鎖
+
+    let matches: Vec<Item> = items
+        .iter()
+        .filter(|i| !i.deleted && i.name.to_lowercase().contains(&q))
+        .cloned()
+        .collect(); // 篩選名稱包含搜尋字串且尚未刪除的項目
+    Ok(HttpResponse::Ok().json(matches)) // 返回符合的項目，無符合時為空陣列
+}
+
+/// 依名稱開頭自動完成（GET 請求）
+#[utoipa::path(
+    get,
+    path = "/items/autocomplete",
+    params(AutocompleteQuery),
+    responses(
+        (status = 200, description = "Retrieved up to limit distinct item names starting with prefix, sorted alphabetically", body = [String]),
+        (status = 500, description = "Internal Server Error", body = ErrorResponse)
+    )
+)]
+#[get("/items/autocomplete")]
+async fn autocomplete_items(query: web::Query<AutocompleteQuery>, data: web::Data<AppState>) -> Result<HttpResponse, ApiError> {
+    data.metrics.record(Operation::Read); // 記錄請求次數
+    let prefix = query.prefix.to_lowercase(); // 轉為小寫以進行不分大小寫的比對
+    let items = data.read_items()?; // 獲取讀取鎖
+
+    let mut names: Vec<&str> = items
+        .iter()
+        .filter(|i| !i.deleted && i.name.to_lowercase().starts_with(&prefix))
+        .map(|i| i.name.as_str())
+        .collect(); // 篩選名稱以 prefix 開頭的項目
+    names.sort_by_cached_key(|name| (name.to_lowercase(), name.to_string())); // 依字母順序排序，不分大小寫
+    names.dedup(); // 多個項目使用相同名稱時只返回一次
+    names.truncate(query.limit.unwrap_or(DEFAULT_AUTOCOMPLETE_LIMIT));
+    Ok(HttpResponse::Ok().json(names)) // 返回符合的名稱，無符合時為空陣列
+}
+
+/// 一次獲取多個項目（GET 請求）
+#[utoipa::path(
+    get,
+    path = "/items/batch",
+    params(BatchQuery),
+    responses(
+        (status = 200, description = "Retrieved the requested items in the requested order, skipping unknown and deleted ids", body = [Item]),
+        (status = 400, description = "Invalid id or more than 100 ids", body = ErrorResponse),
+        (status = 500, description = "Internal Server Error", body = ErrorResponse)
+    )
+)]
+#[get("/items/batch")]
+async fn get_items_batch(query: web::Query<BatchQuery>, data: web::Data<AppState>) -> Result<HttpResponse, ApiError> {
+    data.metrics.record(Operation::Read); // 記錄請求次數
+    let ids: Vec<usize> = query
+        .ids
+        .split(',')
+        .map(|id| id.trim().parse::<usize>().map_err(|_| ApiError::Validation(format!("invalid id: {}", id))))
+        .collect::<Result<_, _>>()?;
+    if ids.len() > MAX_BATCH_IDS {
+        return Err(ApiError::Validation(format!("at most {} ids can be requested at once", MAX_BATCH_IDS)));
+    }
+    let items = data.read_items()?; // 獲取讀取鎖
+
+    let found: Vec<Item> = ids
+        .iter()
+        .filter_map(|id| items.get(*id).filter(|i| !i.deleted))
+        .cloned()
+        .collect(); // 依請求的順序返回，略過不存在或已刪除的 ID
+    Ok(HttpResponse::Ok().json(found))
+}
+
+/// 以 Server-Sent Events 推送項目變更（GET 請求）
+#[utoipa::path(
+    get,
+    path = "/items/events",
+    responses(
+        (status = 200, description = "Stream of item change events such as {\"type\": \"created\", \"id\": 5}", body = String, content_type = "text/event-stream")
+    )
+)]
+#[get("/items/events")]
+async fn item_events(data: web::Data<AppState>) -> impl Responder {
+    let receiver = data.events.subscribe(); // 訂閱之後發生的變更
+    // 客戶端斷線時 actix 會丟棄串流，同時取消訂閱
+    let events = stream::unfold(receiver, |mut receiver| async move {
+        loop {
+            match receiver.recv().await {
+                Ok(event) => {
+                    let json = serde_json::to_string(&event).unwrap_or_default();
+                    let frame = web::Bytes::from(format!("data: {}\n\n", json));
+                    return Some((Ok::<_, actix_web::Error>(frame), receiver));
+                }
+                Err(broadcast::error::RecvError::Lagged(_)) => continue, // 落後太多時略過遺失的事件
+                Err(broadcast::error::RecvError::Closed) => return None,
+            }
+        }
+    });
+    HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header((header::CACHE_CONTROL, "no-cache"))
+        .insert_header(ContentEncoding::Identity) // 不壓縮事件串流，避免壓縮緩衝延遲推送
+        .streaming(events)
+}
+
+/// 以 WebSocket 推送項目變更並接受指令（GET 請求）
+#[utoipa::path(
+    get,
+    path = "/ws",
+    responses(
+        (status = 101, description = "Upgraded to a WebSocket that sends the same change events as /items/events as text frames, and answers the text command \"get <id>\" with the item or an ErrorResponse"),
+        (status = 400, description = "The request is not a valid WebSocket handshake")
+    )
+)]
+#[get("/ws")]
+async fn item_socket(req: HttpRequest, body: web::Payload, data: web::Data<AppState>) -> Result<HttpResponse, actix_web::Error> {
+    let (response, session, messages) = actix_ws::handle(&req, body)?; // 完成握手，之後由背景任務處理連線
+    actix_web::rt::spawn(run_item_socket(session, messages, data));
+    Ok(response)
+}
+
+// WebSocket 連線的主迴圈：同時等待項目變更事件及客戶端訊息，任一方關閉時結束
+async fn run_item_socket(mut session: actix_ws::Session, mut messages: actix_ws::MessageStream, data: web::Data<AppState>) {
+    let mut receiver = data.events.subscribe(); // 訂閱之後發生的變更，與 SSE 共用同一個廣播通道
+    let reason = loop {
+        let event = pin!(receiver.recv());
+        let message = pin!(messages.recv());
+        let next = future::select(event, message).await; // 兩者都可以安全取消，未完成的一方在下一輪重新等待
+        let sent = match next {
+            Either::Left((Ok(event), _)) => session.text(serde_json::to_string(&event).unwrap_or_default()).await,
+            Either::Left((Err(broadcast::error::RecvError::Lagged(_)), _)) => continue, // 落後太多時略過遺失的事件
+            Either::Left((Err(broadcast::error::RecvError::Closed), _)) => break None,
+            Either::Right((Some(Ok(message)), _)) => match message {
+                actix_ws::Message::Text(command) => session.text(socket_command(&command, &data)).await,
+                actix_ws::Message::Ping(bytes) => session.pong(&bytes).await, // 回應客戶端的心跳
+                actix_ws::Message::Close(reason) => break reason, // 客戶端要求關閉
+                _ => Ok(()), // 忽略二進位訊息及 pong
+            },
+            Either::Right((Some(Err(e)), _)) => {
+                log::warn!("websocket protocol error: {}", e);
+                break None;
+            }
+            Either::Right((None, _)) => return, // 客戶端已斷線，不需再送出關閉訊息
+        };
+        if sent.is_err() {
+            return; // 連線已關閉
+        }
+    };
+    let _ = session.close(reason).await; // 回應關閉訊息後結束連線
+}
+
+// 執行 WebSocket 文字指令並返回要送出的 JSON，目前支援 get <id>
+fn socket_command(command: &str, data: &AppState) -> String {
+    let result = match command.split_whitespace().collect::<Vec<_>>().as_slice() {
+        ["get", id] => match id.parse::<usize>() {
+            Ok(id) => data
+                .read_items()
+                .and_then(|items| items.get(id).filter(|i| !i.deleted).cloned().ok_or(ApiError::NotFound)),
+            Err(_) => Err(ApiError::Validation("id must be a non-negative integer".to_string())),
+        },
+        _ => Err(ApiError::Validation(format!("unknown command {:?}, expected \"get <id>\"", command))),
+    };
+    match result {
+        Ok(item) => serde_json::to_string(&item),
+        Err(e) => serde_json::to_string(&ErrorResponse::new(e.code(), e.to_string())),
+    }
+    .unwrap_or_default()
+}
+
+/// 獲取項目數量（GET 請求）
+#[utoipa::path(
+    get,
+    path = "/items/count",
+    responses(
+        (status = 200, description = "Retrieved the number of items, excluding deleted items", body = ItemCount),
+        (status = 500, description = "Internal Server Error", body = ErrorResponse)
+    )
+)]
+#[get("/items/count")]
+async fn count_items(data: web::Data<AppState>) -> Result<HttpResponse, ApiError> {
+    data.metrics.record(Operation::Read); // 記錄請求次數
+    let items = data.read_items()?; // 獲取讀取鎖
+    let count = items.iter().filter(|i| !i.deleted).count(); // 與列表相同，不計算已軟刪除的項目
+    Ok(HttpResponse::Ok().json(ItemCount { count })) // 只返回數量，不需傳送整個列表
+}
+
+/// 獲取項目統計資訊（GET 請求）
+#[utoipa::path(
+    get,
+    path = "/items/stats",
+    responses(
+        (status = 200, description = "Retrieved aggregate statistics of the items", body = ItemStats),
+        (status = 500, description = "Internal Server Error", body = ErrorResponse)
+    )
+)]
+#[get("/items/stats")]
+async fn item_stats(data: web::Data<AppState>) -> Result<HttpResponse, ApiError> {
+    data.metrics.record(Operation::Read); // 記錄請求次數
+    let items = data.read_items()?; // 獲取讀取鎖
+    Ok(HttpResponse::Ok().json(ItemStats::from_items(&items)))
+}
+
+/// 獲取指定版本之後修改過的項目（GET 請求）
+#[utoipa::path(
+    get,
+    path = "/items/changes",
+    params(ChangesQuery),
+    responses(
+        (status = 200, description = "Retrieved the items changed after the since cursor, including soft-deleted items, and the cursor for the next request", body = ItemChanges),
+        (status = 500, description = "Internal Server Error", body = ErrorResponse)
+    )
+)]
+#[get("/items/changes")]
+async fn item_changes(query: web::Query<ChangesQuery>, data: web::Data<AppState>) -> Result<HttpResponse, ApiError> {
+    data.metrics.record(Operation::Read); // 記錄請求次數
+    let since = query.since.unwrap_or(0);
+    let items = data.read_items()?; // 獲取讀取鎖，修改都在寫入鎖內遞增版本號，讀取到的游標與項目一致
+    let mut changed: Vec<Item> = items.iter().filter(|i| i.version > since).cloned().collect();
+    changed.sort_by_key(|i| i.version);
+    Ok(HttpResponse::Ok().json(ItemChanges {
+        items: changed,
+        version: data.change_version.load(Ordering::Acquire),
+    }))
+}
+
+/// 隨機獲取一個項目（GET 請求）
+#[utoipa::path(
+    get,
+    path = "/items/random",
+    responses(
+        (status = 200, description = "Retrieved a uniformly random item, excluding deleted items", body = Item),
+        (status = 404, description = "There are no items"),
+        (status = 500, description = "Internal Server Error", body = ErrorResponse)
+    )
+)]
+#[get("/items/random")]
+async fn random_item(data: web::Data<AppState>) -> Result<HttpResponse, ApiError> {
+    data.metrics.record(Operation::Read); // 記錄請求次數
+    let items = data.read_items()?; // 獲取讀取鎖
+    let active: Vec<&Item> = items.iter().filter(|i| !i.deleted).collect();
+    match active.choose(&mut rand::thread_rng()) { // 每個項目被選中的機率相同
+        Some(item) => Ok(HttpResponse::Ok().json(item)),
+        None => Err(ApiError::NotFound), // 沒有項目時返回 404 Not Found 響應
+    }
+}
+
+/// 獲取所有項目的精簡內容（GET 請求）
+#[utoipa::path(
+    get,
+    path = "/items/summary",
+    responses(
+        (status = 200, description = "Retrieved the id and name of every item, excluding deleted items", body = [ItemSummary]),
+        (status = 500, description = "Internal Server Error", body = ErrorResponse)
+    )
+)]
+#[get("/items/summary")]
+async fn item_summaries(data: web::Data<AppState>) -> Result<HttpResponse, ApiError> {
+    data.metrics.record(Operation::Read); // 記錄請求次數
+    let items = data.read_items()?; // 獲取讀取鎖
+    let summaries: Vec<ItemSummary> = items.iter().filter(|i| !i.deleted).map(ItemSummary::from).collect();
+    Ok(HttpResponse::Ok().json(summaries))
 }
 
-#[derive(Serialize)]
-struct Ram {
-    total_ram: u64,
-    usage_ram: u64,
+/// 依標籤分組獲取項目（GET 請求）
+#[utoipa::path(
+    get,
+    path = "/items/grouped-by-tag",
+    responses(
+        (status = 200, description = "Retrieved an object mapping each tag to the items carrying it, excluding deleted items; items without tags are under \"__untagged__\"", body = ItemsByTag),
+        (status = 500, description = "Internal Server Error", body = ErrorResponse)
+    )
+)]
+#[get("/items/grouped-by-tag")]
+async fn items_grouped_by_tag(data: web::Data<AppState>) -> Result<HttpResponse, ApiError> {
+    data.metrics.record(Operation::Read); // 記錄請求次數
+    let items = data.read_items()?; // 獲取讀取鎖
+
+    let mut groups: BTreeMap<String, Vec<Item>> = BTreeMap::new();
+    for item in items.iter().filter(|i| !i.deleted) { // 只遍歷一次，依項目順序加入每個標籤的分組
+        if item.tags.is_empty() {
+            groups.entry(UNTAGGED_KEY.to_string()).or_default().push(item.clone());
+        }
+        for tag in item.tags.iter().collect::<HashSet<_>>() { // 同一個標籤重複時只加入一次
+            groups.entry(tag.clone()).or_default().push(item.clone());
+        }
+    }
+    Ok(HttpResponse::Ok().json(ItemsByTag(groups)))
 }
 
-#[derive(Serialize)]
-struct CPU {
-    usage_cpu: f32,
+/// 獲取項目校驗碼（GET 請求）
+#[utoipa::path(
+    get,
+    path = "/items/checksum",
+    responses(
+        (status = 200, description = "Retrieved the SHA-256 of the items serialized as in the data file; matches the file once changes are flushed", body = ItemChecksum),
+        (status = 500, description = "Internal Server Error", body = ErrorResponse)
+    )
+)]
+#[get("/items/checksum")]
+async fn item_checksum(data: web::Data<AppState>) -> Result<HttpResponse, ApiError> {
+    data.metrics.record(Operation::Read); // 記錄請求次數
+    let items = data.read_items()?; // 獲取讀取鎖
+    let serialized = serialize_data_file(&items) // 與 save_items 相同的格式，方便與 sha256sum 的結果比對
+        .map_err(|e| ApiError::Internal(format!("unable to serialize items: {}", e)))?;
+    Ok(HttpResponse::Ok().json(ItemChecksum {
+        sha256: format!("{:x}", Sha256::digest(serialized.as_bytes())),
+        count: items.len(),
+    }))
 }
 
-// 定義應用程式狀態，包含一個 Mutex 保護的 Vec<Item>
-struct AppState {
-    items: Mutex<Vec<Item>>,
+/// 以 NDJSON 串流匯出所有項目（GET 請求）
+#[utoipa::path(
+    get,
+    path = "/items/export",
+    responses(
+        (status = 200, description = "Streamed all items except deleted ones, one JSON object per line", body = Item, content_type = "application/x-ndjson"),
+        (status = 500, description = "Internal Server Error", body = ErrorResponse)
+    )
+)]
+#[get("/items/export")]
+async fn export_items(data: web::Data<AppState>) -> Result<HttpResponse, ApiError> {
+    data.metrics.record(Operation::Read); // 記錄請求次數
+    let snapshot: Vec<Item> = data.read_items()?.iter().filter(|i| !i.deleted).cloned().collect(); // 複製尚未刪除的項目後立即釋放資料鎖
+
+    // 逐項序列化為一行 JSON，不會一次持有完整的序列化內容
+    let lines = stream::iter(snapshot.into_iter().map(|item| {
+        let mut line = serde_json::to_vec(&item)?;
+        line.push(b'\n');
+        Ok::<_, serde_json::Error>(web::Bytes::from(line))
+    }));
+    Ok(HttpResponse::Ok()
+        .content_type("application/x-ndjson")
+        .streaming(lines))
 }
 
-// 負責從 JSON 文件讀取項目
-fn load_items() -> Vec<Item> {
-    let path = Path::new("items.json");
-    if !path.exists() {
-        return vec![]; // 如果文件不存在，返回空向量
+// 依 CSV 規則處理欄位：包含逗號、引號或換行時加上引號，並將引號重複一次
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
     }
-
-    let mut file = File::open(path).expect("Unable to open file");
-    let mut contents = String::new();
-    file.read_to_string(&mut contents).expect("Unable to read file");
-    serde_json::from_str(&contents).unwrap_or_else(|_| vec![]) // 將 JSON 解析為 Vec<Item>
 }
 
-// 負責將項目寫入 JSON 文件
-fn save_items(items: &Vec<Item>) -> io::Result<()> {
-    let path = Path::new("items.json");
-    let mut file = OpenOptions::new()
-        .write(true)
-        .create(true)
-        .truncate(true) // 每次寫入時先清空文件
-        .open(path)?;
-    let data = serde_json::to_string(items).expect("Unable to serialize data");
-    file.write_all(data.as_bytes())?;
-    Ok(())
+/// 以 CSV 匯出所有項目（GET 請求）
+#[utoipa::path(
+    get,
+    path = "/items.csv",
+    responses(
+        (status = 200, description = "Exported all items except deleted ones as CSV with an id,name header row", body = String, content_type = "text/csv"),
+        (status = 500, description = "Internal Server Error", body = ErrorResponse)
+    )
+)]
+#[get("/items.csv")]
+async fn export_items_csv(data: web::Data<AppState>) -> Result<HttpResponse, ApiError> {
+    data.metrics.record(Operation::Read); // 記錄請求次數
+    let items = data.read_items()?; // 獲取讀取鎖
+
+    let mut csv = String::from("id,name\r\n"); // 標題列
+    for item in items.iter().filter(|i| !i.deleted) {
+        csv.push_str(&format!("{},{}\r\n", item.id, csv_field(&item.name)));
+    }
+    Ok(HttpResponse::Ok()
+        .content_type("text/csv; charset=utf-8")
+        .insert_header((header::CONTENT_DISPOSITION, "attachment; filename=items.csv")) // 讓瀏覽器下載為文件
+        .body(csv))
 }
 
-/// 創建新項目（POST 請求）
+/// 獲取單一項目（GET 及 HEAD 請求，HEAD 只返回狀態碼及標頭）
 #[utoipa::path(
-    post,
-    path = "/items",
-    request_body = Item,
+    get,
+    path = "/items/{id}",
+    params(
+        ("id" = usize, Path, description = "ID of the item to retrieve")
+    ),
     responses(
-        (status = 201, description = "Created new item successfully", body = Item),
-        (status = 500, description = "Internal Server Error")
+        (status = 200, description = "Retrieved item successfully. Returned as YAML when Accept is application/yaml or text/yaml and the yaml feature is enabled", body = Item,
+            headers(("ETag" = String, description = "Entity tag of the current item state"))),
+        (status = 400, description = "id is not a non-negative integer", body = ErrorResponse),
+        (status = 404, description = "Item not found or deleted"),
+        (status = 500, description = "Internal Server Error", body = ErrorResponse)
     )
 )]
-#[post("/items")]
-async fn create_item(item: web::Json<Item>, data: web::Data<AppState>) -> impl Responder {
-    let mut items = data.items.lock().unwrap(); // 獲取資料鎖
-    items.push(item.into_inner()); // 將新項目添加到 Vec 中
-    save_items(&items).expect("Unable to save items"); // 寫入 JSON 文件
-    HttpResponse::Created().finish() // 返回 201 Created 響應
+#[route("/items/{id}", method = "GET", method = "HEAD")]
+async fn get_item(req: HttpRequest, id: web::Path<usize>, data: web::Data<AppState>) -> Result<HttpResponse, ApiError> {
+    data.metrics.record(Operation::Read); // 記錄請求次數
+    let id = id.into_inner(); // 提取 id
+    let items = data.read_items()?; // 獲取讀取鎖
+
+    if let Some(item) = items.get(id).filter(|i| !i.deleted) { // 以索引查找存在且尚未刪除的項目
+        let mut response = HttpResponse::Ok();
+        response.insert_header((header::ETAG, item_etag(item))); // 提供 ETag 供樂觀並行控制
+        return respond_negotiated(&req, &mut response, item); // 返回 200 OK 及項目
+    }
+    Err(ApiError::NotFound) // 返回 404 Not Found 響應
 }
 
-/// 獲取所有項目（GET 請求）
+/// 獲取項目的直接子項目（GET 請求）
 #[utoipa::path(
     get,
-    path = "/items",
+    path = "/items/{id}/children",
+    params(
+        ("id" = usize, Path, description = "ID of the parent item")
+    ),
     responses(
-        (status = 200, description = "Retrieved all items successfully", body = [Item]),
-        (status = 500, description = "Internal Server Error")
+        (status = 200, description = "Retrieved the items whose parent_id is the given item, excluding deleted items", body = [Item]),
+        (status = 400, description = "id is not a non-negative integer", body = ErrorResponse),
+        (status = 404, description = "Item not found or deleted"),
+        (status = 500, description = "Internal Server Error", body = ErrorResponse)
     )
 )]
-#[get("/items")]
-async fn get_items(data: web::Data<AppState>) -> impl Responder {
-    let items = data.items.lock().unwrap(); // 獲取資料鎖
-    web::Json(items.clone()) // 返回所有項目作為 JSON
+#[get("/items/{id}/children")]
+async fn get_item_children(id: web::Path<usize>, data: web::Data<AppState>) -> Result<HttpResponse, ApiError> {
+    data.metrics.record(Operation::Read); // 記錄請求次數
+    let id = id.into_inner(); // 提取 id
+    let items = data.read_items()?; // 獲取讀取鎖
+
+    if items.get(id).filter(|i| !i.deleted).is_none() { // 檢查父項目是否存在且尚未刪除
+        return Err(ApiError::NotFound); // 返回 404 Not Found 響應
+    }
+    let children: Vec<&Item> = items
+        .iter()
+        .filter(|i| !i.deleted && i.parent_id == Some(id)) // 只返回直接子項目
+        .collect();
+    Ok(HttpResponse::Ok().json(children))
 }
 
 /// 獲取時間（GET 請求）
 #[utoipa::path(
     get,
     path = "/system_info",
+    params(SystemInfoQuery),
     responses(
         (status = 200, description = "Retrieved system info successfully", body = [Info]),
+        (status = 400, description = "Unknown timezone", body = ErrorResponse),
         (status = 500, description = "Internal Server Error")
     )
 )]
 #[get("/system_info")]
-async fn get_system_info(_data: web::Data<AppState>) -> impl Responder {
+async fn get_system_info(query: web::Query<SystemInfoQuery>) -> Result<HttpResponse, ApiError> {
+    let now = Utc::now();
+    let (time, offset) = match query.tz.as_deref() {
+        Some(name) => match name.parse::<Tz>() { // 轉換為指定的時區
+            Ok(tz) => {
+                let zoned = now.with_timezone(&tz);
+                (zoned.to_string(), zoned.offset().fix().to_string())
+            }
+            Err(_) => return Err(ApiError::Validation(format!("unknown timezone: {}", name))),
+        },
+        None => {
+            let local = now.with_timezone(&Local); // 未指定時區時維持原本的本地時間
+            (local.to_string(), local.offset().fix().to_string())
+        }
+    };
+
     let mut sys = System::new_all();
     sys.refresh_all();
 
@@ -128,102 +2410,871 @@ async fn get_system_info(_data: web::Data<AppState>) -> impl Responder {
     // println!("{}%", s.global_cpu_usage());
 
     let usage_cpu = s.global_cpu_usage();
-    let local: DateTime<Local> = Local::now();
-    
-    web::Json(Info {
-        time: local.to_string(),
+
+    Ok(HttpResponse::Ok().json(Info {
+        time,
+        utc: now.to_rfc3339(),
+        offset,
         ram: Ram {
-            total_ram: total_ram,
-            usage_ram: usage_ram,
+            total_ram,
+            usage_ram,
         },
-        cpu: CPU {
-            usage_cpu: usage_cpu,
+        cpu: Cpu {
+            usage_cpu,
+        }
+    }))
+}
+
+/// 存活檢查（GET 請求），不存取資料鎖或文件
+#[utoipa::path(
+    get,
+    path = "/health",
+    responses(
+        (status = 200, description = "Service is alive", body = HealthStatus)
+    )
+)]
+#[get("/health")]
+async fn health() -> impl Responder {
+    HttpResponse::Ok().json(HealthStatus {
+        status: "ok".to_string(),
+    })
+}
+
+// 維護模式的狀態
+#[derive(Serialize, Deserialize, ToSchema)]
+struct MaintenanceStatus {
+    enabled: bool, // 是否拒絕所有修改資料的請求
+}
+
+/// 切換維護模式（POST 請求）
+#[utoipa::path(
+    post,
+    path = "/admin/maintenance",
+    request_body = MaintenanceStatus,
+    responses(
+        (status = 200, description = "Maintenance mode was updated; returns the new state", body = MaintenanceStatus),
+        (status = 400, description = "Malformed JSON body", body = ErrorResponse)
+    )
+)]
+#[post("/admin/maintenance")]
+async fn set_maintenance(status: web::Json<MaintenanceStatus>, mode: web::Data<MaintenanceMode>) -> impl Responder {
+    mode.set(status.enabled);
+    log::info!("maintenance mode {}", if status.enabled { "enabled" } else { "disabled" });
+    HttpResponse::Ok().json(MaintenanceStatus {
+        enabled: mode.is_enabled(),
+    })
+}
+
+/// 從資料文件重新載入項目（POST 請求）
+#[utoipa::path(
+    post,
+    path = "/admin/reload",
+    params(ReloadQuery),
+    responses(
+        (status = 200, description = "Replaced the in-memory items with the contents of the data file; with force=true changes not yet written are discarded", body = ItemCount),
+        (status = 409, description = "Persistence is disabled, there is no data file to reload, or there are acknowledged changes not yet written to the file and force is not true", body = ErrorResponse),
+        (status = 500, description = "The data file could not be read or parsed; the current items are kept", body = ErrorResponse)
+    )
+)]
+#[post("/admin/reload")]
+async fn reload_items(query: web::Query<ReloadQuery>, data: web::Data<AppState>) -> Result<HttpResponse, ApiError> {
+    if !data.persist {
+        return Err(ApiError::Conflict("persistence is disabled, there is no data file to reload".to_string()));
+    }
+    let force = query.force.unwrap_or(false);
+    // 在阻塞執行緒池中讀取文件，讓請求逾時可以生效；讀取前先獲取寫入鎖，直到記錄修改時間後才釋放，
+    // 避免讀取文件到取代項目之間有其他修改被覆蓋，或背景任務寫入的文件被誤認為外部修改
+    let count = web::block(move || -> Result<usize, ApiError> {
+        // 不經過 write_items，因為外部修改後正需要重新載入
+        let mut items = data
+            .items
+            .write()
+            .map_err(|_| ApiError::Internal("item store is unavailable".to_string()))?;
+        // 已回應成功的修改尚未寫入文件時，重新載入會遺失這些修改，需明確以 force=true 捨棄
+        if data.dirty.load(Ordering::Acquire) && !force {
+            return Err(ApiError::Conflict(format!(
+                "there are changes not yet written to {}, reload with force=true to discard them",
+                data.items_file.display()
+            ))); // 返回 409 Conflict 響應
         }
+        *items = load_items(&data.items_file).map_err(|e| ApiError::Internal(e.to_string()))?; // 載入失敗時保留目前的項目
+        data.track_file_mtime(); // 在讀取後記錄，舊格式的文件在載入時會被遷移並寫回
+        data.dirty.store(false, Ordering::Release); // 文件內容即為最新狀態
+        data.change_version.fetch_max(max_version(&items), Ordering::AcqRel); // 計數器不能倒退，否則客戶端的游標會失效
+        data.last_modified.store(unix_now(), Ordering::Release);
+        log::info!("reloaded {} items from {}", items.len(), data.items_file.display());
+        Ok(items.len())
+    })
+    .await
+    .map_err(|e| ApiError::Internal(e.to_string()))??;
+    Ok(HttpResponse::Ok().json(ItemCount { count }))
+}
+
+/// 版本資訊（GET 請求）
+#[utoipa::path(
+    get,
+    path = "/version",
+    responses(
+        (status = 200, description = "Build information of the running server", body = VersionInfo)
+    )
+)]
+#[get("/version")]
+async fn version() -> impl Responder {
+    let build_timestamp = env!("BUILD_TIMESTAMP") // 由 build.rs 在編譯時注入
+        .parse()
+        .ok()
+        .and_then(|secs| DateTime::from_timestamp(secs, 0))
+        .map(|time| time.to_rfc3339())
+        .unwrap_or_default();
+    HttpResponse::Ok().json(VersionInfo {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        git_commit: env!("GIT_COMMIT").to_string(),
+        build_timestamp,
     })
 }
 
+/// 就緒檢查（GET 請求），確認資料狀態可以存取
+#[utoipa::path(
+    get,
+    path = "/ready",
+    responses(
+        (status = 200, description = "Service is ready", body = HealthStatus),
+        (status = 503, description = "Item store is unavailable", body = HealthStatus)
+    )
+)]
+#[get("/ready")]
+async fn ready(data: web::Data<AppState>) -> impl Responder {
+    match data.items.read() { // 嘗試獲取資料鎖
+        Ok(_) => HttpResponse::Ok().json(HealthStatus {
+            status: "ok".to_string(),
+        }),
+        Err(_) => HttpResponse::ServiceUnavailable().json(HealthStatus {
+            status: "unavailable".to_string(),
+        }), // 資料鎖中毒時返回 503
+    }
+}
+
 /// 更新項目（PUT 請求）
 #[utoipa::path(
     put,
     path = "/items/{id}",
     params(
-        ("id" = usize, Path, description = "ID of the item to update")
+        ("id" = usize, Path, description = "ID of the item to update"),
+        ("If-Match" = Option<String>, Header, description = "Only update if the item's current ETag matches"),
+        UpsertQuery
     ),
     request_body = Item,
     responses(
-        (status = 200, description = "Updated item successfully", body = Item),
-        (status = 404, description = "Item not found"),
-        (status = 500, description = "Internal Server Error")
+        (status = 200, description = "Updated an existing item; the body is empty",
+            headers(("ETag" = String, description = "ETag of the updated item"))),
+        (status = 201, description = "Created a new item because it did not exist and upsert was requested", body = Item,
+            headers(("Location" = String, description = "URL of the created item"), ("ETag" = String, description = "ETag of the created item"))),
+        (status = 400, description = "Non-numeric id, invalid item name, missing parent item, parent_id cycle or malformed JSON body", body = ErrorResponse),
+        (status = 413, description = "Request body exceeds MAX_BODY_BYTES", body = ErrorResponse),
+        (status = 404, description = "Item not found or deleted"),
+        (status = 409, description = "The item's version differs from expected_version, upsert targets a deleted item, the body changes the id to one that is already used by another item, or the name is used by another item when UNIQUE_NAMES is enabled", body = ErrorResponse),
+        (status = 412, description = "If-Match does not match the item's current ETag", body = ErrorResponse),
+        (status = 503, description = "Maintenance mode is enabled; retry after the Retry-After delay", body = ErrorResponse),
+        (status = 507, description = "Creating the item with upsert would exceed MAX_ITEMS", body = ErrorResponse),
+        (status = 500, description = "Internal Server Error", body = ErrorResponse)
     )
 )]
 #[put("/items/{id}")]
-async fn update_item(id: web::Path<usize>, item: web::Json<Item>, data: web::Data<AppState>) -> impl Responder {
+async fn update_item(
+    req: HttpRequest,
+    id: web::Path<usize>,
+    query: web::Query<UpsertQuery>,
+    item: web::Json<Item>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError> {
+    data.metrics.record(Operation::Update); // 記錄請求次數
     let id = id.into_inner(); // 提取 id
-    let mut items = data.items.lock().unwrap(); // 獲取資料鎖
+    let mut item = item.into_inner();
+    data.name_normalization.apply(&mut item.name); // 整理名稱中的空白及大小寫
+    validate_item(&item).map_err(ApiError::Validation)?; // 驗證項目內容
+    let mut items = data.write_items()?; // 獲取寫入鎖
 
-    if let Some(existing_item) = items.iter_mut().find(|i| i.id == id) { // 查找存在的項目
+    let id_taken = item.id != id && items.get(item.id).is_some(); // 新的 ID 是否已被其他項目使用
+    if data.unique_names && name_taken(&items, &item.name, id) { // 檢查名稱是否已被其他項目使用
+        return Err(duplicate_name(&item.name)); // 返回 409 Conflict 響應
+    }
+    validate_parent(&parent_links(items.iter()), id, item.parent_id)?; // 檢查父項目是否存在且不會形成循環
+    if let Some(index) = items.position(id) { // 以索引查找存在的項目
+        let existing_item = &mut items[index];
+        if existing_item.deleted { // 已軟刪除的項目需先還原才能更新
+            return Err(if query.upsert.unwrap_or(false) {
+                ApiError::Conflict(format!("item with id {} is deleted; restore it first", id)) // 返回 409 Conflict 響應
+            } else {
+                ApiError::NotFound // 返回 404 Not Found 響應
+            });
+        }
+        if !if_match_satisfied(&req, existing_item) { // 檢查項目是否已被其他人修改
+            return Err(ApiError::PreconditionFailed); // 返回 412 Precondition Failed 響應
+        }
+        if let Some(expected) = query.expected_version.filter(|v| *v != existing_item.version) {
+            return Err(ApiError::Conflict(format!("item version is {}, expected {}", existing_item.version, expected))); // 項目已被其他人修改，返回 409 Conflict 響應
+        }
+        if id_taken {
+            return Err(ApiError::Conflict(format!("item with id {} already exists", item.id))); // 返回 409 Conflict 響應
+        }
+        data.audit("PUT", item.id, Some(&existing_item.name), Some(&item.name)); // 記錄到稽核日誌
         existing_item.name = item.name.clone(); // 更新項目名稱
-        save_items(&items).expect("Unable to save items"); // 寫入 JSON 文件
-        return HttpResponse::Ok().finish(); // 返回 200 OK 響應
+        existing_item.tags = item.tags.clone(); // 更新項目標籤
+        existing_item.parent_id = item.parent_id; // 更新父項目
+        existing_item.updated_at = Local::now(); // 更新修改時間
+        existing_item.version = data.next_version(); // 以全域變更計數器更新版本號
+        items.set_id(index, item.id); // 請求內容中的 ID 與路徑不同時修改 ID，並更新索引
+        let etag = item_etag(&items[index]);
+        if item.id != id {
+            for child in items.iter_mut().filter(|i| i.parent_id == Some(id)) {
+                child.parent_id = Some(item.id); // ID 修改後讓子項目指向新的 ID
+                child.version = data.next_version();
+            }
+        }
+        data.mark_dirty(); // 標記需要寫入，由背景任務寫入 JSON 文件
+        data.publish(ItemEventKind::Updated, item.id); // 通知訂閱者
+        return Ok(HttpResponse::Ok()
+            .insert_header((header::ETAG, etag))
+            .finish()); // 返回 200 OK 響應及新的 ETag
+    }
+
+    if query.upsert.unwrap_or(false) { // 項目不存在且要求 upsert 時創建新項目
+        data.check_capacity(&items, 1)?; // 超過項目數量上限時返回 507 Insufficient Storage 響應
+        let new_item = Item {
+            tags: item.tags.clone(),
+            parent_id: item.parent_id,
+            version: data.next_version(),
+            ..Item::new(id, item.name.clone())
+        };
+        items.push(new_item.clone());
+        data.mark_dirty(); // 標記需要寫入，由背景任務寫入 JSON 文件
+        data.publish(ItemEventKind::Created, id); // 通知訂閱者
+        data.audit("PUT", id, None, Some(&new_item.name)); // 記錄到稽核日誌
+        return Ok(HttpResponse::Created()
+            .insert_header((header::LOCATION, format!("{}/items/{}", data.api_prefix, id))) // 指向新項目的位置
+            .insert_header((header::ETAG, item_etag(&new_item)))
+            .json(new_item)); // 返回 201 Created 響應及新項目，與更新時的 200 區分
+    }
+    Err(ApiError::NotFound) // 返回 404 Not Found 響應
+}
+
+// 依 RFC 7386 將 merge patch 套用到目標：值為 null 的欄位會被移除，未提供的欄位保持不變
+fn merge_patch(target: &mut serde_json::Value, patch: &serde_json::Value) {
+    let serde_json::Value::Object(patch) = patch else {
+        *target = patch.clone(); // 非物件的 patch 直接取代目標
+        return;
+    };
+    if !target.is_object() {
+        *target = serde_json::Value::Object(serde_json::Map::new());
+    }
+    if let serde_json::Value::Object(target) = target {
+        for (key, value) in patch {
+            if value.is_null() {
+                target.remove(key);
+            } else {
+                merge_patch(target.entry(key.clone()).or_insert(serde_json::Value::Null), value);
+            }
+        }
+    }
+}
+
+/// 部分更新項目（PATCH 請求，JSON merge patch 或 JSON Patch）
+#[utoipa::path(
+    patch,
+    path = "/items/{id}",
+    params(
+        ("id" = usize, Path, description = "ID of the item to patch"),
+        ("If-Match" = Option<String>, Header, description = "Only patch if the item's current ETag matches")
+    ),
+    request_body(content = ItemPatch, content_type = "application/merge-patch+json",
+        description = "RFC 7386 merge patch: omitted fields are kept, fields set to null are cleared. With Content-Type application/json-patch+json the body is instead an RFC 6902 JSON Patch array such as [{\"op\": \"replace\", \"path\": \"/name\", \"value\": \"x\"}]"),
+    responses(
+        (status = 200, description = "Patched item successfully", body = Item),
+        (status = 400, description = "Non-numeric id, unsupported JSON Patch op, invalid or missing path, failed test op, patch produces an invalid item, a missing parent item, a parent_id cycle or malformed JSON body", body = ErrorResponse),
+        (status = 413, description = "Request body exceeds MAX_BODY_BYTES", body = ErrorResponse),
+        (status = 404, description = "Item not found or deleted"),
+        (status = 409, description = "The patched name is used by another item when UNIQUE_NAMES is enabled", body = ErrorResponse),
+        (status = 412, description = "If-Match does not match the item's current ETag", body = ErrorResponse),
+        (status = 503, description = "Maintenance mode is enabled; retry after the Retry-After delay", body = ErrorResponse),
+        (status = 500, description = "Internal Server Error", body = ErrorResponse)
+    )
+)]
+#[patch("/items/{id}")]
+async fn patch_item(
+    req: HttpRequest,
+    id: web::Path<usize>,
+    patch: web::Json<serde_json::Value>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError> {
+    data.metrics.record(Operation::Update); // 記錄請求次數
+    let id = id.into_inner(); // 提取 id
+    let mut items = data.write_items()?; // 獲取寫入鎖
+
+    if let Some(index) = items.position(id).filter(|index| !items[*index].deleted) { // 以索引查找存在且尚未刪除的項目
+        let existing_item = &items[index];
+        if !if_match_satisfied(&req, existing_item) { // 檢查項目是否已被其他人修改
+            return Err(ApiError::PreconditionFailed); // 返回 412 Precondition Failed 響應
+        }
+        let mut merged = serde_json::to_value(existing_item)
+            .map_err(|e| ApiError::Internal(format!("unable to serialize item: {}", e)))?;
+        if req.content_type() == "application/json-patch+json" { // 依 Content-Type 選擇 patch 格式
+            json_patch::apply(&mut merged, &patch).map_err(|e| ApiError::Validation(format!("invalid json patch: {}", e)))?;
+        } else {
+            merge_patch(&mut merged, &patch); // 將 patch 合併到目前項目的 JSON 上
+        }
+        let mut patched: Item = serde_json::from_value(merged)
+            .map_err(|e| ApiError::Validation(format!("patch produces an invalid item: {}", e)))?;
+        patched.id = existing_item.id; // ID、創建時間、刪除標記及版本號不能透過 PATCH 修改
+        patched.created_at = existing_item.created_at;
+        patched.deleted = existing_item.deleted;
+        patched.updated_at = Local::now(); // 更新修改時間
+        data.name_normalization.apply(&mut patched.name); // 整理名稱中的空白及大小寫
+        validate_item(&patched).map_err(ApiError::Validation)?; // 驗證更新後的項目內容
+        validate_parent(&parent_links(items.iter()), id, patched.parent_id)?; // 檢查父項目是否存在且不會形成循環
+        if data.unique_names && name_taken(&items, &patched.name, id) { // 檢查名稱是否已被其他項目使用
+            return Err(duplicate_name(&patched.name)); // 返回 409 Conflict 響應
+        }
+        patched.version = data.next_version(); // 版本號由伺服器管理，所有檢查通過後才以全域變更計數器更新
+        data.audit("PATCH", id, Some(&items[index].name), Some(&patched.name)); // 記錄到稽核日誌
+        items[index] = patched.clone();
+        data.mark_dirty(); // 標記需要寫入，由背景任務寫入 JSON 文件
+        data.publish(ItemEventKind::Updated, id); // 通知訂閱者
+        return Ok(HttpResponse::Ok()
+            .insert_header((header::ETAG, item_etag(&patched)))
+            .json(patched)); // 返回 200 OK 響應及更新後的項目
     }
-    HttpResponse::NotFound().finish() // 返回 404 Not Found 響應
+    Err(ApiError::NotFound) // 返回 404 Not Found 響應
 }
 
-/// 刪除項目（DELETE 請求）
+/// 軟刪除項目（DELETE 請求）
 #[utoipa::path(
     delete,
     path = "/items/{id}",
     params(
-        ("id" = usize, Path, description = "ID of the item to delete")
+        ("id" = usize, Path, description = "ID of the item to delete"),
+        DryRunQuery
     ),
     responses(
-        (status = 200, description = "Deleted item successfully"),
-        (status = 404, description = "Item not found"),
-        (status = 500, description = "Internal Server Error")
+        (status = 200, description = "Marked item as deleted; it stays in storage and can be restored. With dry_run=true returns a DryRunResult instead", body = DryRunResult),
+        (status = 400, description = "id is not a non-negative integer", body = ErrorResponse),
+        (status = 404, description = "Item not found or already deleted"),
+        (status = 503, description = "Maintenance mode is enabled; retry after the Retry-After delay", body = ErrorResponse),
+        (status = 500, description = "Internal Server Error", body = ErrorResponse)
     )
 )]
 #[delete("/items/{id}")]
-async fn delete_item(id: web::Path<usize>, data: web::Data<AppState>) -> impl Responder {
+async fn delete_item(id: web::Path<usize>, query: web::Query<DryRunQuery>, data: web::Data<AppState>) -> Result<HttpResponse, ApiError> {
+    data.metrics.record(Operation::Delete); // 記錄請求次數
+    let id = id.into_inner(); // 提取 id
+    let mut items = data.write_items()?; // 獲取寫入鎖
+
+    if let Some(item) = items.get_mut(id).filter(|i| !i.deleted) { // 檢查項目是否存在且尚未刪除
+        if query.dry_run.unwrap_or(false) { // 只預覽，不修改項目
+            return Ok(HttpResponse::Ok().json(DryRunResult {
+                dry_run: true,
+                count: 1,
+                items: vec![item.clone()],
+            }));
+        }
+        item.deleted = true; // 軟刪除：只標記為已刪除，不從列表中移除
+        item.updated_at = Local::now();
+        item.version = data.next_version(); // 讓增量同步的客戶端得知項目已刪除
+        data.mark_dirty(); // 標記需要寫入，由背景任務寫入 JSON 文件
+        data.publish(ItemEventKind::Deleted, id); // 通知訂閱者
+        data.audit("DELETE", id, Some(&item.name), None); // 記錄到稽核日誌
+        return Ok(HttpResponse::Ok().finish()); // 返回 200 OK 響應
+    }
+    Err(ApiError::NotFound) // 返回 404 Not Found 響應
+}
+
+/// 還原已軟刪除的項目（POST 請求）
+#[utoipa::path(
+    post,
+    path = "/items/{id}/restore",
+    params(
+        ("id" = usize, Path, description = "ID of the item to restore")
+    ),
+    responses(
+        (status = 200, description = "Restored item successfully", body = Item),
+        (status = 400, description = "id is not a non-negative integer", body = ErrorResponse),
+        (status = 404, description = "Item not found"),
+        (status = 503, description = "Maintenance mode is enabled; retry after the Retry-After delay", body = ErrorResponse),
+        (status = 507, description = "Restoring the item would exceed MAX_ITEMS", body = ErrorResponse),
+        (status = 500, description = "Internal Server Error", body = ErrorResponse)
+    )
+)]
+#[post("/items/{id}/restore")]
+async fn restore_item(id: web::Path<usize>, data: web::Data<AppState>) -> Result<HttpResponse, ApiError> {
+    data.metrics.record(Operation::Update); // 記錄請求次數
+    let id = id.into_inner(); // 提取 id
+    let mut items = data.write_items()?; // 獲取寫入鎖
+
+    if items.get(id).is_some_and(|item| item.deleted) {
+        data.check_capacity(&items, 1)?; // 還原的項目重新佔用上限，超過時返回 507 Insufficient Storage 響應
+    }
+    if let Some(item) = items.get_mut(id) { // 以索引查找存在的項目
+        if item.deleted {
+            item.deleted = false; // 清除刪除標記
+            item.updated_at = Local::now();
+            item.version = data.next_version();
+            data.mark_dirty(); // 標記需要寫入，由背景任務寫入 JSON 文件
+            data.publish(ItemEventKind::Updated, id); // 通知訂閱者
+            data.audit("POST", id, None, Some(&item.name)); // 記錄到稽核日誌
+        }
+        return Ok(HttpResponse::Ok().json(item.clone())); // 返回 200 OK 及還原後的項目
+    }
+    Err(ApiError::NotFound) // 返回 404 Not Found 響應
+}
+
+/// 調整項目在列表中的順序（POST 請求）
+#[utoipa::path(
+    post,
+    path = "/items/{id}/reorder",
+    params(
+        ("id" = usize, Path, description = "ID of the item to move")
+    ),
+    request_body = ReorderItem,
+    responses(
+        (status = 200, description = "Moved item successfully", body = Item),
+        (status = 400, description = "id is not a non-negative integer", body = ErrorResponse),
+        (status = 404, description = "Item not found"),
+        (status = 503, description = "Maintenance mode is enabled; retry after the Retry-After delay", body = ErrorResponse),
+        (status = 500, description = "Internal Server Error", body = ErrorResponse)
+    )
+)]
+#[post("/items/{id}/reorder")]
+async fn reorder_item(id: web::Path<usize>, reorder: web::Json<ReorderItem>, data: web::Data<AppState>) -> Result<HttpResponse, ApiError> {
+    data.metrics.record(Operation::Update); // 記錄請求次數
     let id = id.into_inner(); // 提取 id
-    let mut items = data.items.lock().unwrap(); // 獲取資料鎖
+    let mut items = data.write_items()?; // 獲取寫入鎖
+
+    let from = match items.position(id) {
+        Some(from) if !items[from].deleted => from,
+        _ => return Err(ApiError::NotFound), // 返回 404 Not Found 響應
+    };
+    // position 只計算其他未刪除的項目，換算為移除項目後列表中的位置，已刪除的項目保持在原本的相對位置
+    let to = items
+        .iter()
+        .enumerate()
+        .filter(|(index, item)| *index != from && !item.deleted)
+        .nth(reorder.position)
+        .map_or(items.len() - 1, |(index, _)| if index > from { index - 1 } else { index });
+    items.move_item(from, to);
+
+    let item = &mut items[to];
+    item.updated_at = Local::now();
+    item.version = data.next_version(); // 讓增量同步的客戶端得知順序已改變
+    data.mark_dirty(); // 標記需要寫入，由背景任務寫入 JSON 文件
+    data.publish(ItemEventKind::Updated, id); // 通知訂閱者
+    data.audit("POST", id, Some(&item.name), Some(&item.name)); // 記錄到稽核日誌
+    Ok(HttpResponse::Ok().json(item.clone())) // 返回 200 OK 及移動後的項目
+}
+
+/// 清空所有項目（DELETE 請求）
+#[utoipa::path(
+    delete,
+    path = "/items",
+    responses(
+        (status = 204, description = "Marked all items as deleted; they stay in storage and can be restored. Not subject to BULK_DELETE_CONFIRM_RATIO"),
+        (status = 503, description = "Maintenance mode is enabled; retry after the Retry-After delay", body = ErrorResponse),
+        (status = 500, description = "Internal Server Error", body = ErrorResponse)
+    )
+)]
+#[delete("/items")]
+async fn delete_all_items(data: web::Data<AppState>) -> Result<HttpResponse, ApiError> {
+    data.metrics.record(Operation::Delete); // 記錄請求次數
+    let mut items = data.write_items()?; // 獲取寫入鎖；明確要求清空所有項目，不需要 confirm=true
+
+    let now = Local::now();
+    for item in items.iter_mut().filter(|i| !i.deleted) {
+        item.deleted = true; // 與單一刪除相同，只標記為已刪除，之後可以還原
+        item.updated_at = now;
+        item.version = data.next_version(); // 讓增量同步的客戶端得知項目已刪除
+        data.publish(ItemEventKind::Deleted, item.id); // 通知訂閱者
+        data.audit("DELETE", item.id, Some(&item.name), None); // 記錄到稽核日誌
+    }
+    data.mark_dirty(); // 標記需要寫入，由背景任務寫入 JSON 文件
+    Ok(HttpResponse::NoContent().finish()) // 返回 204 No Content 響應
+}
+
+// 以 Allow 標頭及 JSON 內容列出資源支援的方法，唯讀模式下省略修改資料的方法
+fn options_response(req: &HttpRequest, methods: &[(&str, &str)], read_only: bool) -> HttpResponse {
+    let methods: Vec<MethodDescription> = methods
+        .iter()
+        .filter(|(method, _)| !read_only || matches!(*method, "GET" | "HEAD" | "OPTIONS"))
+        .map(|(method, description)| MethodDescription {
+            method: method.to_string(),
+            description: description.to_string(),
+        })
+        .collect();
+    let allow = methods.iter().map(|m| m.method.as_str()).collect::<Vec<_>>().join(", ");
+    HttpResponse::Ok().insert_header((header::ALLOW, allow)).json(ResourceOptions {
+        path: req.path().to_string(),
+        methods,
+    })
+}
+
+/// 列出項目集合支援的方法（OPTIONS 請求）
+#[utoipa::path(
+    options,
+    path = "/items",
+    responses(
+        (status = 200, description = "Listed the supported methods in the Allow header and the body; write methods are omitted in read-only mode", body = ResourceOptions,
+            headers(("Allow" = String, description = "Supported methods, e.g. GET, HEAD, POST, DELETE, OPTIONS")))
+    )
+)]
+#[route("/items", method = "OPTIONS")]
+async fn items_options(req: HttpRequest, data: web::Data<AppState>) -> impl Responder {
+    options_response(&req, COLLECTION_METHODS, data.read_only)
+}
+
+/// 列出單一項目支援的方法（OPTIONS 請求）
+#[utoipa::path(
+    options,
+    path = "/items/{id}",
+    params(
+        ("id" = usize, Path, description = "ID of the item")
+    ),
+    responses(
+        (status = 200, description = "Listed the supported methods in the Allow header and the body; write methods are omitted in read-only mode", body = ResourceOptions,
+            headers(("Allow" = String, description = "Supported methods, e.g. GET, HEAD, PUT, PATCH, DELETE, OPTIONS"))),
+        (status = 400, description = "id is not a non-negative integer", body = ErrorResponse)
+    )
+)]
+#[route("/items/{id}", method = "OPTIONS")]
+async fn item_options(req: HttpRequest, _id: web::Path<usize>, data: web::Data<AppState>) -> impl Responder {
+    options_response(&req, ITEM_METHODS, data.read_only)
+}
+
+// 請求（或預檢請求中 Access-Control-Request-Method 要求）的方法是否只讀取資料
+fn is_read_request(head: &RequestHead) -> bool {
+    let method = if head.method == Method::OPTIONS {
+        head.headers
+            .get(header::ACCESS_CONTROL_REQUEST_METHOD)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| Method::from_bytes(value.as_bytes()).ok())
+    } else {
+        Some(head.method.clone())
+    };
+    method.is_none_or(|method| method == Method::GET || method == Method::HEAD)
+}
+
+// 建立 CORS 設定：read_origins 中的來源只能使用 GET 及 HEAD，write_origins 中的來源可以使用所有方法
+// 兩者皆未設定時允許任何來源（開發用）
+fn cors(read_origins: &[String], write_origins: &[String]) -> Cors {
+    let cors = Cors::default()
+        .allowed_methods(vec!["GET", "HEAD", "POST", "PUT", "PATCH", "DELETE"])
+        .allow_any_header()
+        .expose_headers(vec!["X-Total-Count", "ETag", "Location", "X-Request-Id"]) // 讓瀏覽器端可以讀取項目總數、ETag、新項目位置及請求 ID
+        .max_age(3600);
+    if read_origins.is_empty() && write_origins.is_empty() {
+        return cors.allow_any_origin();
+    }
+    let read_origins = read_origins.to_vec();
+    let write_origins = write_origins.to_vec();
+    cors.allowed_origin_fn(move |origin, head| {
+        let Ok(origin) = origin.to_str() else {
+            return false;
+        };
+        // 預檢請求依要求的方法判斷，唯讀來源的寫入預檢會被拒絕，瀏覽器便不會送出實際的請求
+        write_origins.iter().any(|o| o == origin) || (read_origins.iter().any(|o| o == origin) && is_read_request(head))
+    })
+}
+
+/// 輸出 Prometheus 格式的指標（GET 請求）
+#[utoipa::path(
+    get,
+    path = "/metrics",
+    responses(
+        (status = 200, description = "Metrics in Prometheus text exposition format", body = String, content_type = "text/plain"),
+        (status = 500, description = "Internal Server Error", body = ErrorResponse)
+    )
+)]
+#[get("/metrics")]
+async fn metrics(data: web::Data<AppState>) -> Result<HttpResponse, ApiError> {
+    let items_total = data.read_items()?.iter().filter(|i| !i.deleted).count(); // 獲取資料鎖以計算尚未刪除的項目數
+    let m = &data.metrics;
 
-    if items.iter().any(|i| i.id == id) { // 檢查項目是否存在
-        items.retain(|i| i.id != id); // 刪除項目
-        save_items(&items).expect("Unable to save items"); // 寫入 JSON 文件
-        return HttpResponse::Ok().finish(); // 返回 200 OK 響應
+    let mut body = String::new();
+    body.push_str("# HELP http_requests_total Total number of item requests handled.\n");
+    body.push_str("# TYPE http_requests_total counter\n");
+    body.push_str(&format!("http_requests_total {}\n", m.requests_total.load(Ordering::Relaxed)));
+    body.push_str("# HELP item_operations_total Number of item requests by operation.\n");
+    body.push_str("# TYPE item_operations_total counter\n");
+    for (operation, counter) in [
+        ("create", &m.creates_total),
+        ("read", &m.reads_total),
+        ("update", &m.updates_total),
+        ("delete", &m.deletes_total),
+    ] {
+        body.push_str(&format!(
+            "item_operations_total{{operation=\"{}\"}} {}\n",
+            operation,
+            counter.load(Ordering::Relaxed)
+        ));
     }
-    HttpResponse::NotFound().finish() // 返回 404 Not Found 響應
+    body.push_str("# HELP items_total Current number of items, excluding deleted items.\n");
+    body.push_str("# TYPE items_total gauge\n");
+    body.push_str(&format!("items_total {}\n", items_total));
+
+    Ok(HttpResponse::Ok()
+        .content_type("text/plain; version=0.0.4")
+        .body(body))
 }
 
 // 定義 OpenAPI 文檔
 #[derive(OpenApi)]
 #[openapi(
-    paths(get_system_info, create_item, get_items, update_item, delete_item),
-    components(schemas(Item))
+    paths(get_system_info, health, version, set_maintenance, reload_items, ready, metrics, create_item, create_items_bulk, bulk_rename_items, bulk_delete_items, import_items, import_items_ndjson, get_items, search_items, autocomplete_items, get_items_batch, item_events, item_socket, count_items, item_stats, item_changes, random_item, item_summaries, items_grouped_by_tag, item_checksum, export_items, export_items_csv, get_item, get_item_children, update_item, patch_item, delete_item, restore_item, reorder_item, delete_all_items, items_options, item_options),
+    components(schemas(Item, NewItem, ItemPatch, ReorderItem, BulkRename, BulkDelete, DryRunResult, HealthStatus, VersionInfo, MaintenanceStatus, ItemCount, ItemStats, ItemChanges, ItemSummary, ItemsByTag, ResourceOptions, MethodDescription, ItemChecksum, ItemPage, NdjsonImportResult, ErrorResponse))
 )]
 struct ApiDoc;
 
+// 產生 OpenAPI 文檔，並將項目服務的路徑加上 API_PREFIX
+// 唯讀模式下只保留讀取的操作，使用 SQLite 後端時只保留該後端提供的操作
+fn api_doc(prefix: &str, read_only: bool, use_sqlite: bool) -> utoipa::openapi::OpenApi {
+    use utoipa::openapi::PathItemType;
+    let mut openapi = ApiDoc::openapi();
+    if read_only {
+        for item in openapi.paths.paths.values_mut() {
+            item.operations.retain(|kind, _| matches!(kind, PathItemType::Get | PathItemType::Head | PathItemType::Options));
+        }
+        openapi.info.description = Some("Read-only mode: write operations are disabled and return 405".to_string());
+    }
+    #[cfg(feature = "sqlite")]
+    if use_sqlite {
+        for (path, item) in openapi.paths.paths.iter_mut() {
+            item.operations.retain(|kind, _| sqlite::supports(path, kind));
+        }
+    }
+    #[cfg(not(feature = "sqlite"))]
+    let _ = use_sqlite; // 未啟用 sqlite feature 時只有 JSON 文件後端
+    openapi.paths.paths.retain(|_, item| !item.operations.is_empty());
+    if !prefix.is_empty() {
+        openapi.paths.paths = std::mem::take(&mut openapi.paths.paths)
+            .into_iter()
+            .map(|(path, item)| {
+                if path.starts_with("/items") {
+                    (format!("{}{}", prefix, path), item)
+                } else {
+                    (path, item) // 系統服務不受前綴影響
+                }
+            })
+            .collect();
+    }
+    openapi
+}
+
+// 將 OpenAPI 文檔寫入指定的文件，供 CI 產生客戶端程式碼
+fn dump_openapi(path: &Path) -> io::Result<()> {
+    let spec = serde_json::to_string_pretty(&api_doc(&api_prefix(), read_only()?, database_url().is_some()))?;
+    fs::write(path, spec)?;
+    log::info!("wrote OpenAPI document to {}", path.display());
+    Ok(())
+}
+
+// 註冊所有使用 JSON 文件後端的項目服務，固定路徑（例如 /items/search）需在 /items/{id} 之前註冊
+fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(create_item) // 註冊創建項目的服務
+        .service(create_items_bulk) // 註冊批次創建項目的服務
+        .service(bulk_rename_items) // 註冊批次重新命名項目的服務
+        .service(bulk_delete_items) // 註冊批次刪除項目的服務
+        .service(import_items) // 註冊匯入項目的服務
+        .service(import_items_ndjson) // 註冊 NDJSON 串流匯入的服務
+        .service(get_items) // 註冊獲取所有項目的服務
+        .service(search_items) // 註冊搜尋項目的服務
+        .service(autocomplete_items) // 註冊名稱自動完成的服務
+        .service(get_items_batch) // 註冊批次獲取項目的服務
+        .service(item_events) // 註冊項目變更事件的服務
+        .service(item_socket) // 註冊 WebSocket 的服務
+        .service(count_items) // 註冊獲取項目數量的服務
+        .service(item_stats) // 註冊項目統計資訊的服務
+        .service(item_changes) // 註冊增量同步的服務
+        .service(random_item) // 註冊隨機獲取項目的服務
+        .service(item_summaries) // 註冊項目精簡內容的服務
+        .service(items_grouped_by_tag) // 註冊依標籤分組的服務
+        .service(item_checksum) // 註冊項目校驗碼的服務
+        .service(export_items) // 註冊 NDJSON 匯出的服務
+        .service(export_items_csv) // 註冊 CSV 匯出的服務
+        .service(get_item) // 註冊獲取單一項目的服務
+        .service(get_item_children) // 註冊獲取子項目的服務
+        .service(update_item) // 註冊更新項目的服務
+        .service(patch_item) // 註冊部分更新項目的服務
+        .service(delete_item) // 註冊刪除項目的服務
+        .service(restore_item) // 註冊還原項目的服務
+        .service(reorder_item) // 註冊調整項目順序的服務
+        .service(delete_all_items) // 註冊清空所有項目的服務
+        .service(items_options) // 註冊列出項目集合支援方法的服務
+        .service(item_options); // 註冊列出單一項目支援方法的服務
+}
+
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
-    let items = load_items(); // 從 JSON 文件加載項目
-    let app_state = web::Data::new(AppState {
-        items: Mutex::new(items), // 初始化應用程序狀態
-    });
+    // 初始化日誌，日誌等級由 RUST_LOG 環境變數控制，預設為 info
+    env_logger::init_from_env(env_logger::Env::default().default_filter_or("info"));
+
+    // 使用 --dump-openapi <path> 時只輸出 OpenAPI 文檔，不啟動伺服器
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--dump-openapi" {
+            let path = args.next().ok_or_else(|| {
+                io::Error::new(io::ErrorKind::InvalidInput, "--dump-openapi requires a file path")
+            })?;
+            return dump_openapi(Path::new(&path));
+        }
+    }
+    let config = ServerConfig::from_env()?; // 從環境變數讀取綁定位址
+    let api_prefix = api_prefix(); // 項目服務的路徑前綴
+    // 設定 DATABASE_URL 時改用 SQLite 後端，不讀寫 JSON 資料文件
+    let json_backend = config.database_url.is_none();
+    let persist = config.persist && json_backend;
+
+    let items_file = items_file_path(); // 解析資料文件路徑
+    let data_lock = if persist {
+        Some(lock_data_file(&items_file)?) // 確保沒有其他實例使用同一個資料文件
+    } else {
+        None // 停用持久化時完全不存取文件
+    };
+
+    let app_state = {
+        // 從 JSON 文件加載項目，文件損壞時拒絕啟動以免覆蓋原有資料；停用持久化時從空列表開始
+        // 資料文件尚未存在時改從 SEED_DIR 載入種子資料，並在第一次寫入時建立資料文件
+        let seeded = persist && !items_file.exists();
+        let items = if seeded {
+            ItemStore::new(load_seed(&seed_dir_path()))
+        } else if persist {
+            load_items(&items_file).inspect_err(|e| log::error!("{}", e))?
+        } else {
+            ItemStore::default()
+        };
+        let seeded = seeded && !items.is_empty();
+        let change_version = max_version(&items);
+        let audit_log = audit_log_path()
+            .map(|path| AuditLog::open(&path))
+            .transpose()
+            .inspect_err(|e| log::error!("unable to open audit log: {}", e))?;
+        web::Data::new(AppState {
+            items: RwLock::new(items), // 初始化應用程序狀態
+            items_file: items_file.clone(),
+            metrics: Metrics::default(),
+            dirty: AtomicBool::new(seeded), // 載入種子資料時需要寫入資料文件
+            last_modified: AtomicU64::new(unix_now()), // 無法得知啟動前的變更時間，以啟動時間為準
+            change_version: AtomicU64::new(change_version), // 從已儲存的最大版本號繼續遞增
+            events: broadcast::channel(256).0, // 訂閱者落後超過 256 個事件時會略過較舊的事件
+            idempotency_keys: Mutex::new(HashMap::new()),
+            idempotency_ttl: config.idempotency_ttl,
+            persist,
+            save_retries: config.save_retries,
+            backup_keep: config.backup_keep,
+            unique_names: config.unique_names,
+            api_prefix: api_prefix.clone(),
+            defaults: ItemDefaults::from_env(),
+            name_normalization: NameNormalization::from_env()?,
+            audit_log,
+            file_mtime: Mutex::new(file_mtime(&items_file)), // 記錄載入時的修改時間
+            max_items: config.max_items,
+            read_only: config.read_only,
+            bulk_delete_confirm_ratio: config.bulk_delete_confirm_ratio,
+        })
+    };
+
+    #[cfg(feature = "sqlite")]
+    let pool = match config.database_url.as_deref() {
+        Some(database_url) => {
+            let pool = sqlite::connect(database_url).await.map_err(io::Error::other)?;
+            Some(web::Data::new(pool))
+        }
+        None => None,
+    };
+
+    let shutdown_state = app_state.clone(); // 保留一份狀態供關閉時寫入
+    if json_backend {
+        spawn_flush_task(app_state.clone()); // 啟動背景寫入任務
+    }
+
+    // 生產環境中限制允許的來源：ALLOWED_ORIGINS 只能讀取，WRITE_ORIGINS 及舊的 ALLOWED_ORIGIN 可以讀寫
+    let read_origins = env_list("ALLOWED_ORIGINS");
+    let write_origins: Vec<String> = env_list("WRITE_ORIGINS")
+        .into_iter()
+        .chain(std::env::var("ALLOWED_ORIGIN").ok().filter(|origin| !origin.is_empty()))
+        .collect();
+    let api_key = api_key();
+    if api_key.is_none() {
+        log::warn!("API_KEY is not set, /admin endpoints accept unauthenticated requests"); // 管理服務可以切換維護模式及捨棄尚未寫入的變更
+    }
 
-    HttpServer::new(move || {
-        App::new()
-            .app_data(app_state.clone()) // 將應用程式狀態傳遞給應用
+    let bind_address = config.bind_address();
+    let openapi = api_doc(&api_prefix, config.read_only, !json_backend); // 項目路徑加上 API_PREFIX 的 OpenAPI 文檔
+    let rate_limiter = Arc::new(RateLimiter::new(config.rate_limit_per_min)); // 所有 worker 共用的限流狀態
+    let maintenance = web::Data::new(MaintenanceMode::new(config.maintenance)); // 所有 worker 共用的維護模式開關
+
+    let server = HttpServer::new(move || {
+        let app = App::new()
+            .wrap(RequestTimeout::new(config.request_timeout)) // 處理函式逾時時返回 503，需在最內層只計算處理時間
+            .wrap(Maintenance::new(maintenance.clone())) // 維護模式下拒絕修改資料的請求
+            .wrap(ReadOnly::new(config.read_only)) // 唯讀模式下拒絕修改資料的請求
+            .wrap(ApiKey::new(api_key.clone())) // 驗證 API 金鑰，需在 CORS 內層以免擋下預檢請求
+            .wrap(RateLimit::new(rate_limiter.clone())) // 限制每個 IP 的請求頻率
+            .wrap(EnvelopeResponses) // 請求帶有 ?envelope=true 時包裝響應內容
+            .wrap(cors(&read_origins, &write_origins)) // 處理跨來源請求及預檢請求
+            .wrap(Compress::default()) // 依 Accept-Encoding 壓縮響應內容
+            .wrap(RequestIdHeader) // 為每個請求附加 X-Request-Id
+            .wrap(Logger::new("%r %s %Dms request_id=%{x-request-id}o")) // 記錄請求方法、路徑、狀態碼、響應時間及請求 ID
+            .app_data(json_config(config.max_body_bytes)) // 統一 JSON 解析錯誤的響應格式
+            .app_data(path_config()) // 項目 ID 不是數字時返回 400
             .service(get_system_info) // 註冊創建項目的服務
-            .service(create_item) // 註冊創建項目的服務
-            .service(get_items) // 註冊獲取所有項目的服務
-            .service(update_item) // 註冊更新項目的服務
-            .service(delete_item) // 註冊刪除項目的服務
-            .service(
-                SwaggerUi::new("/swagger-ui/{_:.*}")
-                    .url("/api-docs/openapi.json", ApiDoc::openapi()),
-            )
+            .app_data(maintenance.clone()) // 將維護模式開關傳遞給應用
+            .service(health) // 註冊存活檢查的服務
+            .service(version) // 註冊版本資訊的服務
+            .service(set_maintenance); // 註冊切換維護模式的服務
+
+        // Swagger UI 需在項目服務之前註冊，前綴為空時項目服務的 scope 會匹配所有路徑
+        let app = app.service(
+            SwaggerUi::new("/swagger-ui/{_:.*}")
+                .url("/api-docs/openapi.json", openapi.clone()),
+        );
+
+        #[cfg(feature = "sqlite")]
+        if let Some(pool) = &pool {
+            return app
+                .app_data(pool.clone()) // 將資料庫連線池傳遞給應用
+                .service(sqlite::ready) // 註冊就緒檢查的服務
+                .service(web::scope(&api_prefix).configure(sqlite::configure)); // 註冊使用 SQLite 的項目服務
+        }
+
+        app.app_data(app_state.clone()) // 將應用程式狀態傳遞給應用
+            .service(ready) // 註冊就緒檢查的服務
+            .service(metrics) // 註冊指標輸出的服務
+            .service(reload_items) // 註冊重新載入資料文件的服務
+            .service(web::scope(&api_prefix).configure(configure)) // 所有項目服務都位於 API_PREFIX 之下
     })
-    .bind("127.0.0.1:8080")? // 綁定到指定的 IP 和端口
-    .run() // 啟動伺服器
-    .await
+    .shutdown_timeout(30); // 收到 SIGINT/SIGTERM 後最多等待 30 秒讓進行中的請求完成
+
+    // 綁定到指定的 IP 和端口，設定憑證時改用 HTTPS
+    #[cfg(feature = "tls")]
+    let mut server = match &config.tls {
+        Some(paths) => server.bind_rustls_0_23(&bind_address, tls::server_config(paths)?)?,
+        None => server.bind(&bind_address)?,
+    };
+    #[cfg(not(feature = "tls"))]
+    let mut server = server.bind(&bind_address)?;
+
+    if let Some(workers) = config.workers {
+        server = server.workers(workers); // 覆寫預設的 worker 數量
+    }
+
+    let scheme = if config.tls.is_some() { "https" } else { "http" };
+    for addr in server.addrs() {
+        log::info!("starting server at {}://{}", scheme, addr); // 記錄實際綁定的位址
+    }
+    server.run().await?; // 啟動伺服器，並在收到 SIGINT/SIGTERM 時優雅關閉
+
+    if json_backend {
+        flush_items(&shutdown_state)?; // 伺服器停止後寫入最終狀態
+    }
+    if let Some(data_lock) = data_lock {
+        data_lock.unlock()?; // 釋放資料文件鎖，讓其他實例可以啟動
+    }
+
+    Ok(())
 }
@@ -0,0 +1,99 @@
+// 維護模式中介軟體：啟用時拒絕所有修改資料的請求並返回 503，讀取請求照常處理
+// 可以透過 MAINTENANCE 環境變數在啟動時啟用，或在執行期間以 POST /admin/maintenance 切換
+use actix_web::body::EitherBody;
+use actix_web::dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::http::{header, Method};
+use actix_web::{web, Error, HttpResponse};
+use std::future::{ready, Future, Ready};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::ErrorResponse;
+
+// 建議客戶端等待多久後重試（秒）
+const RETRY_AFTER_SECS: u64 = 60;
+
+// 維護模式的開關，在所有 worker 之間共享
+pub struct MaintenanceMode {
+    enabled: AtomicBool,
+}
+
+impl MaintenanceMode {
+    pub fn new(enabled: bool) -> Self {
+        MaintenanceMode {
+            enabled: AtomicBool::new(enabled),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Acquire)
+    }
+
+    pub fn set(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Release);
+    }
+}
+
+// 維護模式下仍允許的請求：讀取請求及 /admin 下的管理操作（例如切換維護模式本身）
+fn is_allowed(req: &ServiceRequest) -> bool {
+    matches!(*req.method(), Method::GET | Method::HEAD | Method::OPTIONS) || req.path().starts_with("/admin/")
+}
+
+#[derive(Clone)]
+pub struct Maintenance {
+    mode: web::Data<MaintenanceMode>,
+}
+
+impl Maintenance {
+    pub fn new(mode: web::Data<MaintenanceMode>) -> Self {
+        Maintenance { mode }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for Maintenance
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = MaintenanceMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(MaintenanceMiddleware {
+            service,
+            mode: self.mode.clone(),
+        }))
+    }
+}
+
+pub struct MaintenanceMiddleware<S> {
+    service: S,
+    mode: web::Data<MaintenanceMode>,
+}
+
+impl<S, B> Service<ServiceRequest> for MaintenanceMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        if !self.mode.is_enabled() || is_allowed(&req) {
+            let fut = self.service.call(req);
+            return Box::pin(async move { fut.await.map(ServiceResponse::map_into_left_body) });
+        }
+
+        let response = HttpResponse::ServiceUnavailable()
+            .insert_header((header::RETRY_AFTER, RETRY_AFTER_SECS)) // 告知客戶端需等待的秒數
+            .json(ErrorResponse::new("maintenance", "server is in maintenance mode")); // 返回 503 Service Unavailable 響應
+        Box::pin(ready(Ok(req.into_response(response).map_into_right_body())))
+    }
+}
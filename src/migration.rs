@@ -0,0 +1,100 @@
+// 資料文件的格式遷移：版本 0 的 items.json 只是項目陣列，目前的格式為 {"schema_version": 1, "items": [...]}
+// 載入時依序套用每個版本的轉換，讓舊的資料文件不需要手動修改即可使用
+use chrono::Local;
+use serde_json::{Map, Value};
+
+// 目前的資料文件版本，修改 Item 的欄位時遞增並在 MIGRATIONS 加入對應的轉換
+pub const SCHEMA_VERSION: u64 = 1;
+
+// MIGRATIONS[n] 將版本 n 的項目轉換為版本 n + 1
+const MIGRATIONS: [fn(&mut Value); SCHEMA_VERSION as usize] = [v0_to_v1];
+
+// 解析資料文件的內容，返回轉換為目前版本的項目陣列及文件原本的版本
+pub fn migrate(value: Value) -> Result<(Vec<Value>, u64), String> {
+    let (version, mut items) = match value {
+        Value::Array(items) => (0, items), // 沒有版本標記的舊文件
+        Value::Object(mut file) => {
+            let version = file
+                .get("schema_version")
+                .and_then(Value::as_u64)
+                .ok_or("missing or invalid schema_version")?;
+            match file.remove("items") {
+                Some(Value::Array(items)) => (version, items),
+                _ => return Err("missing or invalid items".to_string()),
+            }
+        }
+        _ => return Err("expected an array of items or an object with schema_version and items".to_string()),
+    };
+    if version > SCHEMA_VERSION {
+        return Err(format!(
+            "schema_version {} is newer than the supported version {}",
+            version, SCHEMA_VERSION
+        ));
+    }
+
+    for migration in &MIGRATIONS[version as usize..] {
+        items.iter_mut().for_each(migration);
+    }
+    Ok((items, version))
+}
+
+// 版本 0 的項目可能缺少之後加入的欄位：補上明確的預設值，缺少的時間以遷移時間為準而不是 1970 年
+// 啟用 camel-case feature 時寫入的舊文件使用 camelCase 名稱，兩種名稱都視為已存在
+fn v0_to_v1(item: &mut Value) {
+    let Some(item) = item.as_object_mut() else {
+        return; // 格式錯誤的項目留給之後的解析回報
+    };
+    let field = |item: &Map<String, Value>, snake: &str, camel: &str| {
+        item.get(snake).or_else(|| item.get(camel)).cloned()
+    };
+    let created_at = field(item, "created_at", "createdAt");
+    let updated_at = field(item, "updated_at", "updatedAt");
+    let now = Value::String(Local::now().to_rfc3339());
+    if created_at.is_none() {
+        item.insert("created_at".to_string(), updated_at.clone().unwrap_or_else(|| now.clone()));
+    }
+    if updated_at.is_none() {
+        item.insert("updated_at".to_string(), created_at.unwrap_or(now));
+    }
+    if field(item, "parent_id", "parentId").is_none() {
+        item.insert("parent_id".to_string(), Value::Null);
+    }
+    item.entry("deleted").or_insert(Value::Bool(false));
+    item.entry("tags").or_insert_with(|| Value::Array(vec![]));
+    item.entry("version").or_insert_with(|| Value::from(1));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn v0_items_gain_the_current_fields() {
+        let (items, version) = migrate(json!([
+            {"id": 1, "name": "a", "created_at": "2024-01-01T00:00:00+00:00"},
+            {"id": 2, "name": "b", "tags": ["x"], "deleted": true}
+        ]))
+        .unwrap();
+        assert_eq!(version, 0);
+        assert_eq!(items[0]["updated_at"], "2024-01-01T00:00:00+00:00"); // 缺少的修改時間沿用創建時間
+        assert_eq!(items[0]["parent_id"], Value::Null);
+        assert_eq!(items[0]["deleted"], false);
+        assert_eq!(items[0]["tags"], json!([]));
+        assert_eq!(items[0]["version"], 1);
+        assert_eq!(items[1]["tags"], json!(["x"])); // 已存在的欄位保持不變
+        assert_eq!(items[1]["deleted"], true);
+        assert!(items[1]["created_at"].is_string());
+    }
+
+    #[test]
+    fn rejects_newer_and_malformed_files() {
+        let (items, version) = migrate(json!({"schema_version": SCHEMA_VERSION, "items": [{"id": 1}]})).unwrap();
+        assert_eq!(version, SCHEMA_VERSION);
+        assert_eq!(items, [json!({"id": 1})]); // 目前版本的文件不需轉換
+        assert!(migrate(json!({"schema_version": SCHEMA_VERSION + 1, "items": []})).is_err());
+        assert!(migrate(json!({"items": []})).is_err());
+        assert!(migrate(json!({"schema_version": 1, "items": {}})).is_err());
+        assert!(migrate(json!("items")).is_err());
+    }
+}
@@ -0,0 +1,185 @@
+// 以來源 IP 為單位的令牌桶限流中介軟體
+// 每個 IP 每分鐘最多 RATE_LIMIT_PER_MIN 個請求，超過時返回 429 及 Retry-After 標頭
+use actix_web::body::EitherBody;
+use actix_web::dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::http::header;
+use actix_web::{Error, HttpResponse};
+use std::collections::HashMap;
+use std::future::{ready, Future, Ready};
+use std::net::IpAddr;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use crate::ErrorResponse;
+
+// 超過這個數量的 IP 時，清除已經回滿的令牌桶以限制記憶體用量
+const MAX_TRACKED_IPS: usize = 10_000;
+
+// 不受限流的路徑：健康檢查
+fn is_exempt(path: &str) -> bool {
+    path == "/health" || path == "/ready"
+}
+
+// 單一 IP 的令牌桶
+struct Bucket {
+    tokens: f64,          // 目前可用的令牌數
+    last_refill: Instant, // 上次補充令牌的時間
+}
+
+// 所有 IP 共用的限流狀態，在所有 worker 之間共享
+pub struct RateLimiter {
+    per_min: u32,                            // 每分鐘允許的請求數，0 表示不限流
+    buckets: Mutex<HashMap<IpAddr, Bucket>>, // 每個 IP 的令牌桶
+}
+
+impl RateLimiter {
+    pub fn new(per_min: u32) -> Self {
+        RateLimiter {
+            per_min,
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    // 嘗試為指定 IP 取得一個令牌，不足時返回需要等待的秒數
+    fn acquire(&self, ip: IpAddr) -> Result<(), u64> {
+        let capacity = self.per_min as f64;
+        let rate = capacity / 60.0; // 每秒補充的令牌數
+        let now = Instant::now();
+        let mut buckets = match self.buckets.lock() {
+            Ok(buckets) => buckets,
+            Err(_) => return Ok(()), // 狀態損壞時不阻擋請求
+        };
+
+        if buckets.len() >= MAX_TRACKED_IPS && !buckets.contains_key(&ip) {
+            buckets.retain(|_, b| b.tokens + now.duration_since(b.last_refill).as_secs_f64() * rate < capacity);
+        }
+        let bucket = buckets.entry(ip).or_insert(Bucket {
+            tokens: capacity,
+            last_refill: now,
+        });
+
+        let elapsed = now.duration_since(bucket.last_refill).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * rate).min(capacity); // 依經過時間補充令牌
+        bucket.last_refill = now;
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(((1.0 - bucket.tokens) / rate).ceil() as u64)
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct RateLimit {
+    limiter: Arc<RateLimiter>,
+}
+
+impl RateLimit {
+    pub fn new(limiter: Arc<RateLimiter>) -> Self {
+        RateLimit { limiter }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for RateLimit
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = RateLimitMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RateLimitMiddleware {
+            service,
+            limiter: self.limiter.clone(),
+        }))
+    }
+}
+
+pub struct RateLimitMiddleware<S> {
+    service: S,
+    limiter: Arc<RateLimiter>,
+}
+
+impl<S, B> Service<ServiceRequest> for RateLimitMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let result = match req.peer_addr() {
+            Some(addr) if self.limiter.per_min > 0 && !is_exempt(req.path()) => self.limiter.acquire(addr.ip()),
+            _ => Ok(()), // 未設定限流、豁免路徑或無法取得來源 IP 時直接通過
+        };
+
+        let retry_after = match result {
+            Ok(()) => {
+                let fut = self.service.call(req);
+                return Box::pin(async move { fut.await.map(ServiceResponse::map_into_left_body) });
+            }
+            Err(retry_after) => retry_after,
+        };
+
+        let response = HttpResponse::TooManyRequests()
+            .insert_header((header::RETRY_AFTER, retry_after)) // 告知客戶端需等待的秒數
+            .json(ErrorResponse::new("too_many_requests", "too many requests")); // 返回 429 Too Many Requests 響應
+        Box::pin(ready(Ok(req.into_response(response).map_into_right_body())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::test::{call_service, init_service, TestRequest};
+    use actix_web::{web, App};
+
+    #[actix_web::test]
+    async fn rejects_requests_over_the_limit() {
+        let limiter = Arc::new(RateLimiter::new(3));
+        let app = init_service(
+            App::new()
+                .wrap(RateLimit::new(limiter))
+                .route("/items", web::get().to(HttpResponse::Ok))
+                .route("/health", web::get().to(HttpResponse::Ok)),
+        )
+        .await;
+        let request = |path: &str, ip: &str| {
+            TestRequest::get().uri(path).peer_addr(format!("{}:5000", ip).parse().unwrap()).to_request()
+        };
+
+        for _ in 0..3 {
+            assert_eq!(call_service(&app, request("/items", "10.0.0.1")).await.status(), 200);
+        }
+        let resp = call_service(&app, request("/items", "10.0.0.1")).await;
+        assert_eq!(resp.status(), 429);
+        let retry_after: u64 = resp.headers().get(header::RETRY_AFTER).unwrap().to_str().unwrap().parse().unwrap();
+        assert!(retry_after >= 1);
+        assert_eq!(call_service(&app, request("/health", "10.0.0.1")).await.status(), 200); // 健康檢查不受限流
+        assert_eq!(call_service(&app, request("/items", "10.0.0.2")).await.status(), 200); // 每個 IP 分開計算
+    }
+
+    #[actix_web::test]
+    async fn zero_disables_the_limit() {
+        let app = init_service(
+            App::new()
+                .wrap(RateLimit::new(Arc::new(RateLimiter::new(0))))
+                .route("/items", web::get().to(HttpResponse::Ok)),
+        )
+        .await;
+        for _ in 0..10 {
+            let req = TestRequest::get().uri("/items").peer_addr("10.0.0.1:5000".parse().unwrap()).to_request();
+            assert_eq!(call_service(&app, req).await.status(), 200);
+        }
+    }
+}
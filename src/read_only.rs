@@ -0,0 +1,105 @@
+// 唯讀模式中介軟體：設定 READ_ONLY=true 時拒絕所有修改資料的請求並返回 405
+// 用於發布固定的資料集，只開放讀取服務
+use actix_web::body::EitherBody;
+use actix_web::dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::http::{header, Method};
+use actix_web::{Error, HttpResponse};
+use std::future::{ready, Future, Ready};
+use std::pin::Pin;
+
+use crate::ErrorResponse;
+
+// 唯讀模式下允許的請求方法
+fn is_read(method: &Method) -> bool {
+    matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS)
+}
+
+#[derive(Clone)]
+pub struct ReadOnly {
+    enabled: bool, // 是否啟用唯讀模式
+}
+
+impl ReadOnly {
+    pub fn new(enabled: bool) -> Self {
+        ReadOnly { enabled }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for ReadOnly
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = ReadOnlyMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(ReadOnlyMiddleware {
+            service,
+            enabled: self.enabled,
+        }))
+    }
+}
+
+pub struct ReadOnlyMiddleware<S> {
+    service: S,
+    enabled: bool,
+}
+
+impl<S, B> Service<ServiceRequest> for ReadOnlyMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        if !self.enabled || is_read(req.method()) {
+            let fut = self.service.call(req);
+            return Box::pin(async move { fut.await.map(ServiceResponse::map_into_left_body) });
+        }
+
+        let response = HttpResponse::MethodNotAllowed()
+            .insert_header((header::ALLOW, "GET, HEAD, OPTIONS")) // 告知客戶端只允許讀取
+            .json(ErrorResponse::new("read_only", "server is in read-only mode; write operations are disabled")); // 返回 405 Method Not Allowed 響應
+        Box::pin(ready(Ok(req.into_response(response).map_into_right_body())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::test::{call_service, init_service, TestRequest};
+    use actix_web::{web, App};
+
+    #[actix_web::test]
+    async fn rejects_writes_when_enabled() {
+        let app = init_service(
+            App::new()
+                .wrap(ReadOnly::new(true))
+                .route("/items", web::get().to(HttpResponse::Ok))
+                .route("/items", web::post().to(HttpResponse::Created)),
+        )
+        .await;
+
+        let resp = call_service(&app, TestRequest::get().uri("/items").to_request()).await;
+        assert_eq!(resp.status(), 200);
+        let resp = call_service(&app, TestRequest::post().uri("/items").to_request()).await;
+        assert_eq!(resp.status(), 405);
+        assert_eq!(resp.headers().get(header::ALLOW).unwrap(), "GET, HEAD, OPTIONS");
+    }
+
+    #[actix_web::test]
+    async fn allows_writes_when_disabled() {
+        let app = init_service(App::new().wrap(ReadOnly::new(false)).route("/items", web::post().to(HttpResponse::Created))).await;
+        let resp = call_service(&app, TestRequest::post().uri("/items").to_request()).await;
+        assert_eq!(resp.status(), 201);
+    }
+}
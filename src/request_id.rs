@@ -0,0 +1,105 @@
+// 請求 ID 中介軟體：沿用客戶端提供的 X-Request-Id，未提供時產生 UUID
+// ID 會存放在請求的 extensions 中，並在響應標頭中返回，方便跨服務追蹤日誌
+use actix_web::dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::http::header::{HeaderName, HeaderValue};
+use actix_web::{Error, HttpMessage};
+use std::future::{ready, Future, Ready};
+use std::pin::Pin;
+use uuid::Uuid;
+
+pub const REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-request-id");
+
+// 客戶端提供的 ID 允許的最大長度
+const MAX_REQUEST_ID_LEN: usize = 128;
+
+// 存放在請求 extensions 中的請求 ID
+#[derive(Clone)]
+pub struct RequestId(pub String);
+
+// 取得客戶端提供的請求 ID，格式不正確時忽略
+fn incoming_request_id(req: &ServiceRequest) -> Option<String> {
+    let value = req.headers().get(REQUEST_ID_HEADER)?.to_str().ok()?;
+    let valid = !value.is_empty()
+        && value.len() <= MAX_REQUEST_ID_LEN
+        && value.chars().all(|c| c.is_ascii_graphic());
+    valid.then(|| value.to_string())
+}
+
+pub struct RequestIdHeader;
+
+impl<S, B> Transform<S, ServiceRequest> for RequestIdHeader
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = RequestIdMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RequestIdMiddleware { service }))
+    }
+}
+
+pub struct RequestIdMiddleware<S> {
+    service: S,
+}
+
+impl<S, B> Service<ServiceRequest> for RequestIdMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let request_id = incoming_request_id(&req).unwrap_or_else(|| Uuid::new_v4().to_string());
+        req.extensions_mut().insert(RequestId(request_id)); // 讓處理函式可以讀取
+        let fut = self.service.call(req);
+
+        Box::pin(async move {
+            let mut res = fut.await?;
+            let value = res
+                .request()
+                .extensions()
+                .get::<RequestId>()
+                .and_then(|id| HeaderValue::from_str(&id.0).ok());
+            if let Some(value) = value {
+                res.headers_mut().insert(REQUEST_ID_HEADER, value); // 在響應中返回請求 ID
+            }
+            Ok(res)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::test::{call_service, init_service, TestRequest};
+    use actix_web::{web, App, HttpResponse};
+
+    #[actix_web::test]
+    async fn echoes_or_generates_the_request_id() {
+        let app = init_service(App::new().wrap(RequestIdHeader).route("/items", web::get().to(HttpResponse::Ok))).await;
+
+        let req = TestRequest::get().uri("/items").insert_header((REQUEST_ID_HEADER, "abc-123")).to_request();
+        let resp = call_service(&app, req).await;
+        assert_eq!(resp.headers().get(REQUEST_ID_HEADER).unwrap(), "abc-123");
+
+        let resp = call_service(&app, TestRequest::get().uri("/items").to_request()).await;
+        let generated = resp.headers().get(REQUEST_ID_HEADER).unwrap().to_str().unwrap().to_string();
+        assert_eq!(generated.len(), 36); // UUID 的文字格式
+        let resp = call_service(&app, TestRequest::get().uri("/items").to_request()).await;
+        assert_ne!(resp.headers().get(REQUEST_ID_HEADER).unwrap(), generated.as_str());
+
+        let req = TestRequest::get().uri("/items").insert_header((REQUEST_ID_HEADER, "has space")).to_request();
+        let resp = call_service(&app, req).await;
+        assert_ne!(resp.headers().get(REQUEST_ID_HEADER).unwrap(), "has space"); // 格式不正確時改為產生新的 ID
+    }
+}
@@ -0,0 +1,541 @@
+// SQLite 儲存後端（需啟用 sqlite feature 並設定 DATABASE_URL），以參數化查詢取代 JSON 文件
+// 只提供項目的基本 CRUD 服務，與 JSON 後端相同的路徑使用相同的請求及響應格式
+// 其他服務不會被註冊，OpenAPI 文檔也只保留 OPERATIONS 中列出的操作
+use actix_web::http::header;
+use actix_web::{delete, get, patch, post, put, web, HttpRequest, HttpResponse, Responder};
+use chrono::{DateTime, Local};
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePool, SqlitePoolOptions};
+use std::str::FromStr;
+use utoipa::openapi::PathItemType;
+
+use crate::{
+    if_match_satisfied, item_etag, merge_patch, parse_sort, validate_item, ApiError, HealthStatus, Item,
+    ListQuery, NewItem, SearchQuery, SortField, UpsertQuery,
+};
+
+// 使用 SQLite 後端時提供的路徑及方法，包含與 JSON 後端共用的系統服務
+const OPERATIONS: &[(&str, &[PathItemType])] = &[
+    ("/system_info", &[PathItemType::Get]),
+    ("/health", &[PathItemType::Get]),
+    ("/version", &[PathItemType::Get]),
+    ("/ready", &[PathItemType::Get]),
+    ("/admin/maintenance", &[PathItemType::Post]),
+    ("/items", &[PathItemType::Get, PathItemType::Post, PathItemType::Delete]),
+    ("/items/bulk", &[PathItemType::Post]),
+    ("/items/search", &[PathItemType::Get]),
+    ("/items/{id}", &[PathItemType::Get, PathItemType::Put, PathItemType::Patch, PathItemType::Delete]),
+    ("/items/{id}/restore", &[PathItemType::Post]),
+];
+
+// OpenAPI 文檔中的操作是否由 SQLite 後端提供
+pub fn supports(path: &str, operation: &PathItemType) -> bool {
+    OPERATIONS
+        .iter()
+        .any(|(supported, operations)| *supported == path && operations.contains(operation))
+}
+
+// 連線到 DATABASE_URL 指定的資料庫，並確保 items 資料表存在
+// 較早建立的資料表沒有 deleted 欄位，連線時補上，既有的資料列視為尚未刪除
+pub async fn connect(database_url: &str) -> Result<SqlitePool, sqlx::Error> {
+    let options = SqliteConnectOptions::from_str(database_url)?.create_if_missing(true);
+    let pool = SqlitePoolOptions::new().connect_with(options).await?;
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS items (
+            id INTEGER PRIMARY KEY,
+            name TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL,
+            deleted INTEGER NOT NULL DEFAULT 0
+        )",
+    )
+    .execute(&pool)
+    .await?;
+    let has_deleted: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM pragma_table_info('items') WHERE name = 'deleted'")
+        .fetch_one(&pool)
+        .await?;
+    if has_deleted == 0 {
+        sqlx::query("ALTER TABLE items ADD COLUMN deleted INTEGER NOT NULL DEFAULT 0")
+            .execute(&pool)
+            .await?;
+    }
+    Ok(pool)
+}
+
+// 註冊所有使用 SQLite 的項目服務，/items/search 需在 /items/{id} 之前註冊
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(create_item)
+        .service(create_items_bulk)
+        .service(get_items)
+        .service(search_items)
+        .service(get_item)
+        .service(update_item)
+        .service(patch_item)
+        .service(delete_item)
+        .service(restore_item)
+        .service(delete_all_items);
+}
+
+// 查詢項目時選取的欄位
+const ITEM_COLUMNS: &str = "id, name, created_at, updated_at, deleted";
+
+// 資料庫中 items 資料表的一列
+#[derive(sqlx::FromRow)]
+struct ItemRow {
+    id: i64,
+    name: String,
+    created_at: DateTime<Local>,
+    updated_at: DateTime<Local>,
+    deleted: bool,
+}
+
+// 將資料庫中的一列轉換為 Item
+fn to_item(row: ItemRow) -> Item {
+    Item {
+        id: row.id as usize,
+        name: row.name,
+        created_at: row.created_at,
+        updated_at: row.updated_at,
+        deleted: row.deleted,
+        tags: vec![],    // SQLite 後端尚未儲存標籤，提供標籤的請求返回 400
+        version: 1,      // SQLite 後端尚未儲存版本號，expected_version 返回 400
+        parent_id: None, // SQLite 後端尚未儲存父項目，提供父項目的請求返回 400
+    }
+}
+
+// 插入新項目，未指定 ID 時綁定 NULL 由 SQLite 分配下一個 ID，返回實際的 ID
+async fn insert_item<'e, E>(executor: E, id: Option<usize>, item: &Item) -> Result<usize, sqlx::Error>
+where
+    E: sqlx::Executor<'e, Database = sqlx::Sqlite>,
+{
+    let result = sqlx::query("INSERT INTO items (id, name, created_at, updated_at) VALUES (?, ?, ?, ?)")
+        .bind(id.map(|id| id as i64))
+        .bind(&item.name)
+        .bind(item.created_at)
+        .bind(item.updated_at)
+        .execute(executor)
+        .await?;
+    Ok(result.last_insert_rowid() as usize)
+}
+
+// 將資料庫錯誤轉換為 500 錯誤
+fn database_error(e: sqlx::Error) -> ApiError {
+    ApiError::Internal(format!("database error: {}", e))
+}
+
+// 判斷錯誤是否為主鍵衝突
+fn is_conflict(e: &sqlx::Error) -> bool {
+    matches!(e, sqlx::Error::Database(db) if db.is_unique_violation())
+}
+
+fn conflict(id: usize) -> ApiError {
+    ApiError::Conflict(format!("item with id {} already exists", id))
+}
+
+// upsert 的目標已被軟刪除，需先還原
+fn deleted_conflict(id: usize) -> ApiError {
+    ApiError::Conflict(format!("item with id {} is deleted; restore it first", id))
+}
+
+#[get("/ready")]
+pub async fn ready(pool: web::Data<SqlitePool>) -> impl Responder {
+    match sqlx::query("SELECT 1").execute(pool.get_ref()).await { // 確認資料庫可以存取
+        Ok(_) => HttpResponse::Ok().json(HealthStatus {
+            status: "ok".to_string(),
+        }),
+        Err(_) => HttpResponse::ServiceUnavailable().json(HealthStatus {
+            status: "unavailable".to_string(),
+        }),
+    }
+}
+
+// SQLite 後端尚未儲存標籤及父項目，請求中提供時返回 400 而不是丟棄
+fn unsupported_fields(tags: &[String], parent_id: Option<usize>) -> Result<(), ApiError> {
+    let field = if !tags.is_empty() {
+        "tags"
+    } else if parent_id.is_some() {
+        "parent_id"
+    } else {
+        return Ok(());
+    };
+    Err(ApiError::Validation(format!("{} is not supported by the SQLite backend", field)))
+}
+
+// 新項目的位置，與請求相同的路徑前綴加上 ID，例如 /items/1
+fn item_location(req: &HttpRequest, id: usize) -> String {
+    format!("{}/{}", req.path().trim_end_matches('/'), id)
+}
+
+#[post("/items")]
+async fn create_item(req: HttpRequest, item: web::Json<NewItem>, pool: web::Data<SqlitePool>) -> Result<HttpResponse, ApiError> {
+    let item = item.into_inner();
+    unsupported_fields(&item.tags, item.parent_id)?;
+    let mut new_item = Item::new(item.id.unwrap_or(0), item.name);
+    validate_item(&new_item).map_err(ApiError::Validation)?; // 驗證項目內容
+
+    new_item.id = insert_item(pool.get_ref(), item.id, &new_item)
+        .await
+        .map_err(|e| if is_conflict(&e) { conflict(new_item.id) } else { database_error(e) })?;
+    Ok(HttpResponse::Created()
+        .insert_header((header::LOCATION, item_location(&req, new_item.id)))
+        .json(new_item))
+}
+
+#[post("/items/bulk")]
+async fn create_items_bulk(
+    new_items: web::Json<Vec<NewItem>>,
+    pool: web::Data<SqlitePool>,
+) -> Result<HttpResponse, ApiError> {
+    let mut tx = pool.begin().await.map_err(database_error)?; // 在單一交易中寫入所有項目
+
+    let mut created = Vec::with_capacity(new_items.len());
+    for new_item in new_items.into_inner() {
+        unsupported_fields(&new_item.tags, new_item.parent_id)?; // 任何一個無效則整個交易回滾
+        let mut item = Item::new(new_item.id.unwrap_or(0), new_item.name);
+        validate_item(&item).map_err(ApiError::Validation)?; // 任何一個無效則整個交易回滾
+        item.id = insert_item(&mut *tx, new_item.id, &item)
+            .await
+            .map_err(|e| if is_conflict(&e) { conflict(item.id) } else { database_error(e) })?;
+        created.push(item);
+    }
+
+    tx.commit().await.map_err(database_error)?;
+    Ok(HttpResponse::Created().json(created))
+}
+
+// SQLite 後端尚未支援的查詢參數，提供時返回 400 而不是忽略
+fn unsupported_param(query: &ListQuery) -> Option<&'static str> {
+    [
+        ("name", query.name.is_some()),
+        ("fields", query.fields.is_some()),
+        ("tag", query.tag.is_some()),
+        ("from", query.from.is_some()),
+        ("to", query.to.is_some()),
+        ("paged", query.paged.is_some()),
+    ]
+    .into_iter()
+    .find(|(_, provided)| *provided)
+    .map(|(name, _)| name)
+}
+
+#[get("/items")]
+async fn get_items(query: web::Query<ListQuery>, pool: web::Data<SqlitePool>) -> Result<HttpResponse, ApiError> {
+    if let Some(name) = unsupported_param(&query) {
+        return Err(ApiError::Validation(format!("{} is not supported by the SQLite backend", name)));
+    }
+    let offset = query.offset.unwrap_or(0);
+    let limit = query.limit.unwrap_or(50);
+    let (sort_field, descending) = parse_sort(&query).map_err(ApiError::Validation)?;
+    let sort_column = match sort_field { // 欄位名稱來自固定選項，可以安全地組合進 SQL
+        SortField::Position | SortField::Id => "id", // SQLite 後端不支援調整順序，依創建順序（ID）返回
+        SortField::Name => "name",
+    };
+    let direction = if descending { "DESC" } else { "ASC" };
+
+    let filter = if query.include_deleted.unwrap_or(false) { "" } else { "WHERE deleted = 0" }; // 預設隱藏已軟刪除的項目
+    let total: i64 = sqlx::query_scalar(&format!("SELECT COUNT(*) FROM items {}", filter))
+        .fetch_one(pool.get_ref())
+        .await
+        .map_err(database_error)?;
+    let sql = format!(
+        "SELECT {} FROM items {} ORDER BY {} {} LIMIT ? OFFSET ?",
+        ITEM_COLUMNS, filter, sort_column, direction
+    );
+    let rows: Vec<ItemRow> = sqlx::query_as(&sql)
+        .bind(limit as i64)
+        .bind(offset as i64)
+        .fetch_all(pool.get_ref())
+        .await
+        .map_err(database_error)?;
+
+    let page: Vec<Item> = rows.into_iter().map(to_item).collect();
+    Ok(HttpResponse::Ok()
+        .insert_header(("X-Total-Count", total))
+        .json(page))
+}
+
+#[get("/items/search")]
+async fn search_items(
+    query: web::Query<SearchQuery>,
+    pool: web::Data<SqlitePool>,
+) -> Result<HttpResponse, ApiError> {
+    let sql = format!("SELECT {} FROM items WHERE deleted = 0 AND instr(lower(name), ?) > 0 ORDER BY id", ITEM_COLUMNS);
+    let rows: Vec<ItemRow> = sqlx::query_as(&sql)
+        .bind(query.q.to_lowercase())
+        .fetch_all(pool.get_ref())
+        .await
+        .map_err(database_error)?;
+    let matches: Vec<Item> = rows.into_iter().map(to_item).collect();
+    Ok(HttpResponse::Ok().json(matches))
+}
+
+// 依 ID 查詢單一項目，包含已軟刪除的項目
+async fn fetch_item(pool: &SqlitePool, id: usize) -> Result<Option<Item>, sqlx::Error> {
+    let sql = format!("SELECT {} FROM items WHERE id = ?", ITEM_COLUMNS);
+    let row: Option<ItemRow> = sqlx::query_as(&sql)
+        .bind(id as i64)
+        .fetch_optional(pool)
+        .await?;
+    Ok(row.map(to_item))
+}
+
+#[get("/items/{id}")]
+async fn get_item(id: web::Path<usize>, pool: web::Data<SqlitePool>) -> Result<HttpResponse, ApiError> {
+    match fetch_item(pool.get_ref(), id.into_inner()).await.map_err(database_error)?.filter(|i| !i.deleted) {
+        Some(item) => Ok(HttpResponse::Ok()
+            .insert_header((header::ETAG, item_etag(&item))) // 與 JSON 後端相同，提供 ETag 供 If-Match 使用
+            .json(item)),
+        None => Err(ApiError::NotFound),
+    }
+}
+
+#[put("/items/{id}")]
+async fn update_item(
+    req: HttpRequest,
+    id: web::Path<usize>,
+    query: web::Query<UpsertQuery>,
+    item: web::Json<Item>,
+    pool: web::Data<SqlitePool>,
+) -> Result<HttpResponse, ApiError> {
+    let id = id.into_inner();
+    validate_item(&item).map_err(ApiError::Validation)?;
+    unsupported_fields(&item.tags, item.parent_id)?;
+    if query.expected_version.is_some() {
+        return Err(ApiError::Validation("expected_version is not supported by the SQLite backend".to_string())); // 版本號固定為 1，無法偵測衝突
+    }
+    if req.headers().contains_key(header::IF_MATCH) {
+        let existing = fetch_item(pool.get_ref(), id).await.map_err(database_error)?;
+        if existing.is_some_and(|existing| !if_match_satisfied(&req, &existing)) {
+            return Err(ApiError::PreconditionFailed); // 項目已被其他人修改
+        }
+    }
+
+    let result = sqlx::query("UPDATE items SET name = ?, updated_at = ? WHERE id = ? AND deleted = 0")
+        .bind(&item.name)
+        .bind(Local::now())
+        .bind(id as i64)
+        .execute(pool.get_ref())
+        .await
+        .map_err(database_error)?;
+    if result.rows_affected() > 0 {
+        return Ok(HttpResponse::Ok().finish());
+    }
+
+    if query.upsert.unwrap_or(false) { // 項目不存在且要求 upsert 時創建新項目
+        let new_item = Item::new(id, item.name.clone());
+        insert_item(pool.get_ref(), Some(id), &new_item)
+            .await
+            .map_err(|e| if is_conflict(&e) { deleted_conflict(id) } else { database_error(e) })?; // 只有已軟刪除的項目會佔用此 ID
+        return Ok(HttpResponse::Created()
+            .insert_header((header::LOCATION, req.path().to_string()))
+            .json(new_item));
+    }
+    Err(ApiError::NotFound)
+}
+
+#[patch("/items/{id}")]
+async fn patch_item(
+    req: HttpRequest,
+    id: web::Path<usize>,
+    patch: web::Json<serde_json::Value>,
+    pool: web::Data<SqlitePool>,
+) -> Result<HttpResponse, ApiError> {
+    let existing = fetch_item(pool.get_ref(), id.into_inner())
+        .await
+        .map_err(database_error)?
+        .filter(|i| !i.deleted)
+        .ok_or(ApiError::NotFound)?;
+    if !if_match_satisfied(&req, &existing) {
+        return Err(ApiError::PreconditionFailed); // 項目已被其他人修改
+    }
+    let mut merged = serde_json::to_value(&existing)
+        .map_err(|e| ApiError::Internal(format!("unable to serialize item: {}", e)))?;
+    merge_patch(&mut merged, &patch); // 與 JSON 後端相同，以 JSON merge patch 更新
+    let mut item: Item = serde_json::from_value(merged)
+        .map_err(|e| ApiError::Validation(format!("patch produces an invalid item: {}", e)))?;
+    item.id = existing.id;
+    item.created_at = existing.created_at;
+    item.updated_at = Local::now();
+    validate_item(&item).map_err(ApiError::Validation)?;
+    unsupported_fields(&item.tags, item.parent_id)?;
+
+    sqlx::query("UPDATE items SET name = ?, updated_at = ? WHERE id = ?")
+        .bind(&item.name)
+        .bind(item.updated_at)
+        .bind(item.id as i64)
+        .execute(pool.get_ref())
+        .await
+        .map_err(database_error)?;
+    Ok(HttpResponse::Ok()
+        .insert_header((header::ETAG, item_etag(&item)))
+        .json(item))
+}
+
+// 與 JSON 後端相同使用軟刪除，只標記為已刪除
+#[delete("/items/{id}")]
+async fn delete_item(id: web::Path<usize>, pool: web::Data<SqlitePool>) -> Result<HttpResponse, ApiError> {
+    let result = sqlx::query("UPDATE items SET deleted = 1, updated_at = ? WHERE id = ? AND deleted = 0")
+        .bind(Local::now())
+        .bind(id.into_inner() as i64)
+        .execute(pool.get_ref())
+        .await
+        .map_err(database_error)?;
+    if result.rows_affected() == 0 {
+        return Err(ApiError::NotFound);
+    }
+    Ok(HttpResponse::Ok().finish())
+}
+
+#[post("/items/{id}/restore")]
+async fn restore_item(id: web::Path<usize>, pool: web::Data<SqlitePool>) -> Result<HttpResponse, ApiError> {
+    let id = id.into_inner();
+    sqlx::query("UPDATE items SET deleted = 0, updated_at = ? WHERE id = ? AND deleted = 1")
+        .bind(Local::now())
+        .bind(id as i64)
+        .execute(pool.get_ref())
+        .await
+        .map_err(database_error)?;
+    match fetch_item(pool.get_ref(), id).await.map_err(database_error)? { // 未刪除的項目直接返回
+        Some(item) => Ok(HttpResponse::Ok().json(item)),
+        None => Err(ApiError::NotFound),
+    }
+}
+
+#[delete("/items")]
+async fn delete_all_items(pool: web::Data<SqlitePool>) -> Result<HttpResponse, ApiError> {
+    sqlx::query("UPDATE items SET deleted = 1, updated_at = ? WHERE deleted = 0")
+        .bind(Local::now())
+        .execute(pool.get_ref())
+        .await
+        .map_err(database_error)?;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{test, App};
+
+    // 在記憶體資料庫上建立只包含 SQLite 項目服務的應用
+    macro_rules! sqlite_app {
+        () => {{
+            let pool = connect("sqlite::memory:").await.expect("in-memory database");
+            test::init_service(App::new().app_data(web::Data::new(pool)).configure(configure)).await
+        }};
+    }
+
+    #[actix_web::test]
+    async fn supports_only_registered_operations() {
+        assert!(supports("/items", &PathItemType::Post));
+        assert!(supports("/items/{id}", &PathItemType::Patch));
+        assert!(supports("/items/{id}/restore", &PathItemType::Post));
+        assert!(!supports("/items/{id}/reorder", &PathItemType::Post));
+        assert!(!supports("/items/export", &PathItemType::Get));
+    }
+
+    #[actix_web::test]
+    async fn crud_round_trip() {
+        let app = sqlite_app!();
+
+        let req = test::TestRequest::post().uri("/items").set_json(serde_json::json!({"name": "first"})).to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), 201);
+        assert_eq!(resp.headers().get(header::LOCATION).unwrap(), "/items/1");
+        let created: Item = test::read_body_json(resp).await;
+        assert_eq!(created.name, "first");
+
+        let resp = test::call_service(&app, test::TestRequest::get().uri("/items/1").to_request()).await;
+        assert_eq!(resp.status(), 200);
+        let etag = resp.headers().get(header::ETAG).unwrap().clone();
+
+        let req = test::TestRequest::patch()
+            .uri("/items/1")
+            .insert_header((header::IF_MATCH, etag.clone()))
+            .set_json(serde_json::json!({"name": "renamed"}))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), 200);
+        let patched: Item = test::read_body_json(resp).await;
+        assert_eq!(patched.name, "renamed");
+
+        let req = test::TestRequest::get().uri("/items").to_request();
+        let items: Vec<Item> = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].name, "renamed");
+
+        let resp = test::call_service(&app, test::TestRequest::delete().uri("/items/1").to_request()).await;
+        assert_eq!(resp.status(), 200);
+        let resp = test::call_service(&app, test::TestRequest::get().uri("/items/1").to_request()).await;
+        assert_eq!(resp.status(), 404);
+    }
+
+    #[actix_web::test]
+    async fn delete_is_soft_and_restorable() {
+        let app = sqlite_app!();
+        let req = test::TestRequest::post().uri("/items").set_json(serde_json::json!({"name": "kept"})).to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), 201);
+        let resp = test::call_service(&app, test::TestRequest::delete().uri("/items").to_request()).await;
+        assert_eq!(resp.status(), 204);
+
+        let items: Vec<Item> = test::call_and_read_body_json(&app, test::TestRequest::get().uri("/items").to_request()).await;
+        assert!(items.is_empty());
+        let req = test::TestRequest::get().uri("/items?include_deleted=true").to_request();
+        let items: Vec<Item> = test::call_and_read_body_json(&app, req).await;
+        assert!(items[0].deleted);
+        let req = test::TestRequest::put().uri("/items/1?upsert=true").set_json(serde_json::json!({"id": 1, "name": "x"})).to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), 409);
+
+        let req = test::TestRequest::post().uri("/items/1/restore").to_request();
+        let restored: Item = test::call_and_read_body_json(&app, req).await;
+        assert!(!restored.deleted);
+        let resp = test::call_service(&app, test::TestRequest::get().uri("/items/1").to_request()).await;
+        assert_eq!(resp.status(), 200);
+    }
+
+    #[actix_web::test]
+    async fn duplicate_id_is_a_conflict() {
+        let app = sqlite_app!();
+        let item = serde_json::json!({"id": 7, "name": "seven"});
+        let req = test::TestRequest::post().uri("/items").set_json(&item).to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), 201);
+        let req = test::TestRequest::post().uri("/items").set_json(&item).to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), 409);
+    }
+
+    #[actix_web::test]
+    async fn update_and_upsert() {
+        let app = sqlite_app!();
+        let item = serde_json::json!({"id": 3, "name": "three"});
+        let req = test::TestRequest::put().uri("/items/3").set_json(&item).to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), 404);
+        let req = test::TestRequest::put().uri("/items/3?upsert=true").set_json(&item).to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), 201);
+        let req = test::TestRequest::put()
+            .uri("/items/3")
+            .insert_header((header::IF_MATCH, "\"stale\""))
+            .set_json(&item)
+            .to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), 412);
+    }
+
+    #[actix_web::test]
+    async fn unsupported_list_params_are_rejected() {
+        let app = sqlite_app!();
+        let req = test::TestRequest::get().uri("/items?tag=red").to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), 400);
+    }
+
+    #[actix_web::test]
+    async fn unstored_fields_are_rejected() {
+        let app = sqlite_app!();
+        let req = test::TestRequest::post().uri("/items").set_json(serde_json::json!({"name": "a", "tags": ["red"]})).to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), 400);
+        let req = test::TestRequest::post().uri("/items/bulk").set_json(serde_json::json!([{"name": "a", "parent_id": 1}])).to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), 400);
+        let req = test::TestRequest::post().uri("/items").set_json(serde_json::json!({"name": "a"})).to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), 201);
+        let req = test::TestRequest::patch().uri("/items/1").set_json(serde_json::json!({"tags": ["red"]})).to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), 400);
+        let req = test::TestRequest::put().uri("/items/1?expected_version=1").set_json(serde_json::json!({"id": 1, "name": "b"})).to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), 400);
+    }
+}
@@ -0,0 +1,135 @@
+// 項目儲存：在項目列表旁維護 ID 到列表位置的索引，讓以 ID 查找項目為 O(1)
+// 只能透過這裡的方法修改列表，索引才會與列表保持一致；讀取時可以直接當作 Vec<Item> 使用
+use std::collections::HashMap;
+use std::ops::{Deref, Index, IndexMut};
+
+use crate::Item;
+
+#[derive(Default)]
+pub(crate) struct ItemStore {
+    items: Vec<Item>,             // 依列表順序保存的項目，新項目加在最後
+    index: HashMap<usize, usize>, // 項目 ID 對應其在 items 中的位置
+}
+
+impl ItemStore {
+    // 從項目列表建立儲存及索引
+    pub fn new(items: Vec<Item>) -> Self {
+        let mut store = ItemStore {
+            items,
+            index: HashMap::new(),
+        };
+        store.reindex();
+        store
+    }
+
+    // 重建索引，ID 重複時指向第一個項目，與依序查找的結果相同
+    fn reindex(&mut self) {
+        self.index.clear();
+        for (position, item) in self.items.iter().enumerate().rev() {
+            self.index.insert(item.id, position);
+        }
+    }
+
+    // 項目在列表中的位置
+    pub fn position(&self, id: usize) -> Option<usize> {
+        self.index.get(&id).copied()
+    }
+
+    // 以 ID 查找項目
+    pub fn get(&self, id: usize) -> Option<&Item> {
+        self.position(id).map(|position| &self.items[position])
+    }
+
+    // 以 ID 查找項目並修改，不可透過返回的參考修改 ID，修改 ID 時使用 set_id
+    pub fn get_mut(&mut self, id: usize) -> Option<&mut Item> {
+        self.position(id).map(|position| &mut self.items[position])
+    }
+
+    // 修改指定位置的項目 ID 並更新索引
+    pub fn set_id(&mut self, position: usize, id: usize) {
+        let old_id = std::mem::replace(&mut self.items[position].id, id);
+        if self.index.get(&old_id) == Some(&position) {
+            self.index.remove(&old_id);
+        }
+        self.index.entry(id).or_insert(position);
+    }
+
+    // 在列表結尾新增項目
+    pub fn push(&mut self, item: Item) {
+        self.index.entry(item.id).or_insert(self.items.len());
+        self.items.push(item);
+    }
+
+    // 在列表結尾新增多個項目
+    pub fn extend(&mut self, items: impl IntoIterator<Item = Item>) {
+        for item in items {
+            self.push(item);
+        }
+    }
+
+    // 將項目從一個位置移到另一個位置，其間的項目依序移動一格，並重建索引
+    pub fn move_item(&mut self, from: usize, to: usize) {
+        let item = self.items.remove(from);
+        self.items.insert(to, item);
+        self.reindex();
+    }
+
+    // 逐一修改項目，不可修改 ID
+    pub fn iter_mut(&mut self) -> std::slice::IterMut<'_, Item> {
+        self.items.iter_mut()
+    }
+}
+
+impl Deref for ItemStore {
+    type Target = Vec<Item>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.items
+    }
+}
+
+impl Index<usize> for ItemStore {
+    type Output = Item;
+
+    fn index(&self, position: usize) -> &Item {
+        &self.items[position]
+    }
+}
+
+// 以位置修改項目，不可修改 ID
+impl IndexMut<usize> for ItemStore {
+    fn index_mut(&mut self, position: usize) -> &mut Item {
+        &mut self.items[position]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(id: usize) -> Item {
+        Item::new(id, format!("item-{}", id))
+    }
+
+    #[test]
+    fn index_follows_inserts_id_changes_and_moves() {
+        let mut store = ItemStore::new((1..=500).map(item).collect());
+        store.extend((501..=1000).map(item));
+        for position in (0..1000).step_by(7) {
+            let id = store[position].id;
+            store.set_id(position, id + 10_000);
+        }
+        store.move_item(999, 0);
+        store.move_item(10, 500);
+        store.push(item(2000));
+
+        assert_eq!(store.len(), 1001);
+        for (position, item) in store.iter().enumerate() {
+            assert_eq!(store.position(item.id), Some(position));
+        }
+        assert!(store.get(1).is_none()); // 位置 0 的項目 ID 已改為 10001
+        assert_eq!(store.get(10_001).map(|i| i.name.as_str()), Some("item-1"));
+        assert_eq!(store[0].id, 1000);
+        assert_eq!(store.get(2000).map(|i| i.id), Some(2000));
+    }
+}
@@ -0,0 +1,2036 @@
+// 項目服務的整合測試：以 actix_web::test 呼叫與 main 相同註冊的服務
+use super::*;
+use actix_web::body::MessageBody;
+use actix_web::test::{call_and_read_body, call_and_read_body_json, call_service, init_service, read_body, TestRequest};
+
+// 測試用的暫存目錄，結束時刪除
+struct TempDir(PathBuf);
+
+impl TempDir {
+    fn new() -> Self {
+        static COUNTER: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+        let dir = std::env::temp_dir().join(format!(
+            "rust_api-test-{}-{}",
+            std::process::id(),
+            COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed)
+        ));
+        std::fs::create_dir_all(&dir).expect("create temp dir");
+        TempDir(dir)
+    }
+
+    fn path(&self, name: &str) -> PathBuf {
+        self.0.join(name)
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+// 修改環境變數的測試需依序執行，避免互相影響
+static ENV_LOCK: Mutex<()> = Mutex::new(());
+
+fn env_lock() -> std::sync::MutexGuard<'static, ()> {
+    ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+// 名稱為 item-<id> 的測試項目
+fn items(ids: impl IntoIterator<Item = usize>) -> Vec<Item> {
+    ids.into_iter().map(|id| Item::new(id, format!("item-{}", id))).collect()
+}
+
+// 讀取資料文件中的項目
+fn saved_items(path: &Path) -> Vec<Item> {
+    parse_data_file(&fs::read(path).unwrap()).unwrap().0
+}
+
+// 以 JSON 內容送出的 POST 請求
+fn post_json(uri: &str, body: serde_json::Value) -> TestRequest {
+    TestRequest::post().uri(uri).set_json(body)
+}
+
+// 依序取出項目的 ID，方便比較返回的列表
+fn ids(items: &[Item]) -> Vec<usize> {
+    items.iter().map(|i| i.id).collect()
+}
+
+// 只保存在記憶體中的應用程式狀態
+fn test_state(items: Vec<Item>) -> AppState {
+    state_with_file(items, PathBuf::from("items.json"), false)
+}
+
+// 使用指定資料文件的應用程式狀態，persist 為 true 時讀寫該文件
+fn state_with_file(items: Vec<Item>, items_file: PathBuf, persist: bool) -> AppState {
+    let change_version = max_version(&items);
+    AppState {
+        items: RwLock::new(ItemStore::new(items)),
+        file_mtime: Mutex::new(file_mtime(&items_file)),
+        items_file,
+        metrics: Metrics::default(),
+        dirty: AtomicBool::new(false),
+        last_modified: AtomicU64::new(unix_now()),
+        change_version: AtomicU64::new(change_version),
+        events: broadcast::channel(16).0,
+        idempotency_keys: Mutex::new(HashMap::new()),
+        idempotency_ttl: Duration::from_secs(60),
+        persist,
+        save_retries: 0,
+        backup_keep: 0,
+        unique_names: false,
+        api_prefix: String::new(),
+        defaults: ItemDefaults { tags: vec![], name_prefix: None },
+        name_normalization: NameNormalization { collapse_whitespace: false, lowercase: false },
+        audit_log: None,
+        max_items: None,
+        read_only: false,
+        bulk_delete_confirm_ratio: 1.0,
+    }
+}
+
+// 以 main 中相同的設定建立測試服務
+macro_rules! app {
+    ($state:expr) => {
+        init_service(
+            App::new()
+                .app_data(json_config(64 * 1024))
+                .app_data(path_config())
+                .app_data($state.clone())
+                .service(ready)
+                .service(metrics)
+                .service(reload_items)
+                .service(web::scope("").configure(configure)),
+        )
+        .await
+    };
+}
+
+// 以 multipart/form-data 上傳文件的請求
+fn upload(uri: &str, contents: &str) -> TestRequest {
+    let boundary = "test-boundary";
+    let body = format!(
+        "--{b}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"items.json\"\r\nContent-Type: application/json\r\n\r\n{contents}\r\n--{b}--\r\n",
+        b = boundary
+    );
+    TestRequest::post()
+        .uri(uri)
+        .insert_header((header::CONTENT_TYPE, format!("multipart/form-data; boundary={}", boundary)))
+        .set_payload(body)
+}
+
+// 標記為已軟刪除的項目
+fn deleted(mut item: Item) -> Item {
+    item.deleted = true;
+    item
+}
+
+// 指定父項目的測試項目
+fn child(id: usize, parent_id: usize) -> Item {
+    Item {
+        parent_id: Some(parent_id),
+        ..Item::new(id, format!("item-{}", id))
+    }
+}
+
+#[actix_web::test]
+async fn duplicate_id_is_a_conflict() {
+    let state = web::Data::new(test_state(items([1])));
+    let app = app!(state);
+
+    let req = TestRequest::post().uri("/items").set_json(serde_json::json!({"id": 1, "name": "second"})).to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), 409);
+    let items = state.items.read().unwrap();
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0].name, "item-1");
+}
+
+#[actix_web::test]
+async fn poisoned_lock_returns_500() {
+    let state = web::Data::new(test_state(items([1])));
+    let app = app!(state);
+
+    let poisoned = state.clone();
+    let _ = std::thread::spawn(move || {
+        let _items = poisoned.items.write().unwrap();
+        panic!("poison the item store"); // 持有寫入鎖時崩潰
+    })
+    .join();
+
+    let resp = call_service(&app, TestRequest::get().uri("/items").to_request()).await;
+    assert_eq!(resp.status(), 500);
+    let req = TestRequest::post().uri("/items").set_json(serde_json::json!({"name": "new"})).to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), 500);
+    let body: serde_json::Value = serde_json::from_slice(&read_body(resp).await).unwrap();
+    assert_eq!(body["code"], "internal_error");
+    assert_eq!(body["message"], "item store is unavailable");
+}
+
+#[actix_web::test]
+async fn failed_save_keeps_the_changes_dirty() {
+    let dir = TempDir::new();
+    // 以 root 執行時唯讀權限無法阻擋寫入，改以目錄作為資料文件的路徑
+    let state = web::Data::new(state_with_file(vec![], dir.0.clone(), true));
+    let app = app!(state);
+
+    let req = post_json("/items", serde_json::json!({"name": "new"})).to_request();
+    assert_eq!(call_service(&app, req).await.status(), 201);
+    assert!(state.flush_if_dirty().is_err());
+    assert!(state.dirty.load(Ordering::Acquire)); // 保留標記以便下次重試
+}
+
+#[actix_web::test]
+async fn items_persist_at_the_configured_path() {
+    let dir = TempDir::new();
+    let path = dir.path("custom.json");
+    let items_file = {
+        let _env = env_lock();
+        std::env::set_var("ITEMS_FILE", &path);
+        let items_file = items_file_path();
+        std::env::remove_var("ITEMS_FILE");
+        items_file
+    };
+    assert_eq!(items_file, path);
+
+    let state = web::Data::new(state_with_file(vec![], items_file, true));
+    let app = app!(state);
+    let req = TestRequest::post().uri("/items").set_json(serde_json::json!({"name": "saved"})).to_request();
+    assert_eq!(call_service(&app, req).await.status(), 201);
+    state.flush_if_dirty().unwrap();
+    assert_eq!(saved_items(&path).iter().map(|i| i.name.as_str()).collect::<Vec<_>>(), ["saved"]);
+    assert_eq!(load_items(&path).unwrap().len(), 1);
+}
+
+#[actix_web::test]
+async fn out_of_range_offset_returns_an_empty_page() {
+    let state = web::Data::new(test_state(items(1..=3)));
+    let app = app!(state);
+
+    let page: Vec<Item> = call_and_read_body_json(&app, TestRequest::get().uri("/items?offset=10").to_request()).await;
+    assert!(page.is_empty());
+    let page: Vec<Item> = call_and_read_body_json(&app, TestRequest::get().uri("/items?limit=1&offset=1").to_request()).await;
+    assert_eq!(page.iter().map(|i| i.id).collect::<Vec<_>>(), [2]);
+    let page: Vec<Item> = call_and_read_body_json(&app, TestRequest::get().uri("/items").to_request()).await;
+    assert_eq!(page.len(), 3);
+}
+
+#[actix_web::test]
+async fn empty_patch_leaves_the_item_unchanged() {
+    let dir = TempDir::new();
+    let mut stored = items([1]);
+    stored[0].tags = vec!["red".to_string()];
+    let state = web::Data::new(state_with_file(stored, dir.path("items.json"), true));
+    let app = app!(state);
+
+    let req = TestRequest::patch().uri("/items/1").set_json(serde_json::json!({})).to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+    let patched: Item = serde_json::from_slice(&read_body(resp).await).unwrap();
+    assert_eq!(patched.name, "item-1");
+    assert_eq!(patched.tags, ["red"]);
+
+    let req = TestRequest::patch().uri("/items/1").set_json(serde_json::json!({"name": "renamed"})).to_request();
+    let patched: Item = call_and_read_body_json(&app, req).await;
+    assert_eq!(patched.name, "renamed");
+    let req = TestRequest::patch().uri("/items/9").set_json(serde_json::json!({})).to_request();
+    assert_eq!(call_service(&app, req).await.status(), 404); // 與 PUT 相同
+}
+
+#[actix_web::test]
+async fn delete_all_empties_the_list_in_memory_and_on_disk() {
+    let dir = TempDir::new();
+    let path = dir.path("items.json");
+    let state = web::Data::new(state_with_file(vec![], path.clone(), true));
+    let app = app!(state);
+
+    for name in ["a", "b"] {
+        let req = post_json("/items", serde_json::json!({ "name": name })).to_request();
+        assert_eq!(call_service(&app, req).await.status(), 201);
+    }
+    let resp = call_service(&app, TestRequest::delete().uri("/items").to_request()).await;
+    assert_eq!(resp.status(), 204);
+    let listed: Vec<Item> = call_and_read_body_json(&app, TestRequest::get().uri("/items").to_request()).await;
+    assert!(listed.is_empty());
+
+    state.flush_if_dirty().unwrap();
+    let saved = saved_items(&path);
+    assert_eq!(saved.len(), 2); // 軟刪除的項目保留在文件中以便還原
+    assert!(saved.iter().all(|i| i.deleted));
+}
+
+#[actix_web::test]
+async fn bulk_create_is_saved_in_one_write() {
+    let dir = TempDir::new();
+    let path = dir.path("items.json");
+    let state = web::Data::new(state_with_file(vec![], path.clone(), true));
+    let app = app!(state);
+
+    let new_items: Vec<_> = (0..100).map(|n| serde_json::json!({"name": format!("bulk-{}", n)})).collect();
+    let req = post_json("/items/bulk", serde_json::Value::Array(new_items)).to_request();
+    let created: Vec<Item> = call_and_read_body_json(&app, req).await;
+    assert_eq!(created.iter().map(|i| i.id).collect::<Vec<_>>(), (1..=100).collect::<Vec<_>>());
+    assert!(!path.exists()); // 請求本身不寫入文件
+    state.flush_if_dirty().unwrap(); // 一次寫入所有項目
+    assert_eq!(saved_items(&path).len(), 100);
+
+    let req = post_json("/items/bulk", serde_json::json!([{"id": 1, "name": "dup"}, {"name": "x"}])).to_request();
+    assert_eq!(call_service(&app, req).await.status(), 409);
+    assert!(!state.dirty.load(Ordering::Acquire)); // 衝突時沒有任何變更
+}
+
+#[test]
+fn failed_save_keeps_the_original_file() {
+    let dir = TempDir::new();
+    let path = dir.path("items.json");
+    save_items(&path, &items(1..=2), 0).unwrap();
+    std::fs::create_dir(dir.path("items.json.tmp")).unwrap(); // 暫存文件無法寫入，模擬中途失敗
+    assert!(save_items(&path, &items(1..=5), 0).is_err());
+    assert_eq!(saved_items(&path).iter().map(|i| i.id).collect::<Vec<_>>(), [1, 2]);
+}
+
+#[actix_web::test]
+async fn invalid_names_are_rejected() {
+    let dir = TempDir::new();
+    let state = web::Data::new(state_with_file(items([1]), dir.path("items.json"), true));
+    let app = app!(state);
+
+    for name in [String::new(), "   ".to_string(), "x".repeat(MAX_NAME_LEN + 1)] {
+        let req = post_json("/items", serde_json::json!({ "name": name })).to_request();
+        assert_eq!(call_service(&app, req).await.status(), 400);
+        let req = TestRequest::put().uri("/items/1").set_json(serde_json::json!({"id": 1, "name": name})).to_request();
+        assert_eq!(call_service(&app, req).await.status(), 400);
+        let req = TestRequest::patch().uri("/items/1").set_json(serde_json::json!({ "name": name })).to_request();
+        assert_eq!(call_service(&app, req).await.status(), 400);
+    }
+    assert_eq!(state.items.read().unwrap()[0].name, "item-1");
+    let req = post_json("/items", serde_json::json!({"name": "x".repeat(MAX_NAME_LEN)})).to_request();
+    assert_eq!(call_service(&app, req).await.status(), 201);
+}
+
+#[actix_web::test]
+async fn upsert_creates_or_updates() {
+    let dir = TempDir::new();
+    let path = dir.path("items.json");
+    let state = web::Data::new(state_with_file(vec![], path.clone(), true));
+    let app = app!(state);
+
+    let req = TestRequest::put().uri("/items/5?upsert=true").set_json(serde_json::json!({"id": 5, "name": "created"})).to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), 201);
+    assert_eq!(resp.headers().get(header::LOCATION).unwrap(), "/items/5");
+    assert!(resp.headers().contains_key(header::ETAG));
+    let req = TestRequest::put().uri("/items/5?upsert=true").set_json(serde_json::json!({"id": 5, "name": "updated"})).to_request();
+    assert_eq!(call_service(&app, req).await.status(), 200);
+    state.flush_if_dirty().unwrap();
+    assert_eq!(saved_items(&path).iter().map(|i| i.name.as_str()).collect::<Vec<_>>(), ["updated"]);
+    let req = TestRequest::put().uri("/items/6").set_json(serde_json::json!({"id": 6, "name": "missing"})).to_request();
+    assert_eq!(call_service(&app, req).await.status(), 404);
+    assert_eq!(state.items.read().unwrap().len(), 1);
+}
+
+#[actix_web::test]
+async fn x_total_count_matches_the_stored_items() {
+    let state = web::Data::new(test_state(items(1..=5)));
+    let app = app!(state);
+
+    let resp = call_service(&app, TestRequest::get().uri("/items?limit=1").to_request()).await;
+    assert_eq!(resp.headers().get("X-Total-Count").unwrap(), "5"); // 分頁前的總數
+    let page: Vec<Item> = serde_json::from_slice(&read_body(resp).await).unwrap();
+    assert_eq!(page.len(), 1);
+    let resp = call_service(&app, TestRequest::get().uri("/items?offset=10").to_request()).await;
+    assert_eq!(resp.headers().get("X-Total-Count").unwrap(), "5");
+}
+
+#[actix_web::test]
+async fn shutdown_flushes_the_final_state() {
+    let dir = TempDir::new();
+    let path = dir.path("items.json");
+    let state = state_with_file(items(1..=3), path.clone(), true);
+
+    let server = HttpServer::new(App::new).workers(1).bind(("127.0.0.1", 0)).unwrap().run();
+    let handle = server.handle();
+    let running = actix_web::rt::spawn(server);
+    handle.stop(true).await; // 與收到 SIGTERM 時相同的關閉流程
+    running.await.unwrap().unwrap();
+    flush_items(&state).unwrap();
+    assert_eq!(saved_items(&path).iter().map(|i| i.id).collect::<Vec<_>>(), [1, 2, 3]);
+}
+
+#[actix_web::test]
+async fn update_keeps_created_at_and_refreshes_updated_at() {
+    let dir = TempDir::new();
+    let past = Local::now() - chrono::TimeDelta::seconds(3600);
+    let stored = Item {
+        created_at: past,
+        updated_at: past,
+        ..Item::new(1, "before".to_string())
+    };
+    let state = web::Data::new(state_with_file(vec![stored], dir.path("items.json"), true));
+    let app = app!(state);
+
+    let req = TestRequest::put().uri("/items/1").set_json(Item::new(1, "after".to_string())).to_request();
+    assert_eq!(call_service(&app, req).await.status(), 200);
+    let updated: Item = call_and_read_body_json(&app, TestRequest::get().uri("/items/1").to_request()).await;
+    assert_eq!(updated.name, "after");
+    assert_eq!(updated.created_at, past); // 請求內容中的創建時間被忽略
+    assert!(updated.updated_at > past);
+
+    let req = TestRequest::patch().uri("/items/1").set_json(serde_json::json!({"name": "patched"})).to_request();
+    let patched: Item = call_and_read_body_json(&app, req).await;
+    assert_eq!(patched.created_at, past);
+    assert!(patched.updated_at >= updated.updated_at);
+}
+
+#[actix_web::test]
+async fn list_can_be_sorted_by_name() {
+    let stored = ["banana", "cherry", "apple"].iter().enumerate().map(|(n, name)| Item::new(n + 1, name.to_string())).collect();
+    let state = web::Data::new(test_state(stored));
+    let app = app!(state);
+
+    let req = TestRequest::get().uri("/items?sort_by=name&order=desc").to_request();
+    let listed: Vec<Item> = call_and_read_body_json(&app, req).await;
+    assert_eq!(listed.iter().map(|i| i.name.as_str()).collect::<Vec<_>>(), ["cherry", "banana", "apple"]);
+    let req = TestRequest::get().uri("/items?sort_by=name").to_request();
+    let listed: Vec<Item> = call_and_read_body_json(&app, req).await;
+    assert_eq!(listed.iter().map(|i| i.id).collect::<Vec<_>>(), [3, 1, 2]);
+    let req = TestRequest::get().uri("/items?order=desc&limit=1").to_request();
+    let listed: Vec<Item> = call_and_read_body_json(&app, req).await;
+    assert_eq!(listed.iter().map(|i| i.id).collect::<Vec<_>>(), [3]); // 先排序再分頁
+
+    let resp = call_service(&app, TestRequest::get().uri("/items?sort_by=size").to_request()).await;
+    assert_eq!(resp.status(), 400);
+    let resp = call_service(&app, TestRequest::get().uri("/items?order=random").to_request()).await;
+    assert_eq!(resp.status(), 400);
+}
+
+#[actix_web::test]
+async fn malformed_json_returns_the_error_body() {
+    let state = web::Data::new(test_state(items([1])));
+    let app = app!(state);
+
+    for req in [TestRequest::post().uri("/items"), TestRequest::put().uri("/items/1")] {
+        let req = req.insert_header(("content-type", "application/json")).set_payload("{bad").to_request();
+        let resp = call_service(&app, req).await;
+        assert_eq!(resp.status(), 400);
+        let body: serde_json::Value = serde_json::from_slice(&read_body(resp).await).unwrap();
+        assert_eq!(body["code"], "invalid_json");
+        assert_eq!(body["message"], "invalid json");
+        assert!(body["detail"].is_string());
+    }
+}
+
+#[actix_web::test]
+async fn metrics_count_items_and_operations() {
+    let dir = TempDir::new();
+    let state = web::Data::new(state_with_file(vec![], dir.path("items.json"), true));
+    let app = app!(state);
+
+    for name in ["a", "b"] {
+        let req = post_json("/items", serde_json::json!({ "name": name })).to_request();
+        assert_eq!(call_service(&app, req).await.status(), 201);
+    }
+    assert_eq!(call_service(&app, TestRequest::get().uri("/items/1").to_request()).await.status(), 200);
+    let resp = call_service(&app, TestRequest::get().uri("/metrics").to_request()).await;
+    assert!(resp.headers().get("content-type").unwrap().to_str().unwrap().starts_with("text/plain"));
+    let body = String::from_utf8(read_body(resp).await.to_vec()).unwrap();
+    assert!(body.contains("items_total 2\n"));
+    assert!(body.contains("item_operations_total{operation=\"create\"} 2\n"));
+    assert!(body.contains("item_operations_total{operation=\"read\"} 1\n"));
+    assert!(body.contains("http_requests_total 3\n"));
+}
+
+#[actix_web::test]
+async fn rapid_creates_are_batched_into_one_write() {
+    let dir = TempDir::new();
+    let path = dir.path("items.json");
+    let state = web::Data::new(state_with_file(vec![], path.clone(), true));
+    let app = app!(state);
+
+    for n in 0..50 {
+        let req = post_json("/items", serde_json::json!({"name": format!("rapid-{}", n)})).to_request();
+        assert_eq!(call_service(&app, req).await.status(), 201);
+    }
+    assert!(!path.exists()); // 50 個請求都沒有寫入文件
+    state.flush_if_dirty().unwrap();
+    assert_eq!(saved_items(&path).len(), 50);
+    std::fs::remove_file(&path).unwrap();
+    state.flush_if_dirty().unwrap(); // 沒有新的變更時不再寫入
+    assert!(!path.exists());
+}
+
+#[test]
+fn etag_is_the_sha256_of_the_item() {
+    let item = Item::new(1, "first".to_string());
+    let expected = format!("\"{:x}\"", Sha256::digest(serde_json::to_vec(&item).unwrap()));
+    assert_eq!(item_etag(&item), expected); // 與 /items/checksum 相同的摘要，重新啟動或升級後仍相同
+    assert_ne!(item_etag(&Item::new(1, "second".to_string())), expected);
+}
+
+#[actix_web::test]
+async fn stale_etag_is_rejected() {
+    let state = web::Data::new(test_state(items([1])));
+    let app = app!(state);
+
+    let resp = call_service(&app, TestRequest::get().uri("/items/1").to_request()).await;
+    let etag = resp.headers().get(header::ETAG).unwrap().clone();
+    let req = TestRequest::put()
+        .uri("/items/1")
+        .insert_header((header::IF_MATCH, etag.clone()))
+        .set_json(Item::new(1, "first".to_string()))
+        .to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+    assert_ne!(resp.headers().get(header::ETAG).unwrap(), &etag);
+
+    let req = TestRequest::put()
+        .uri("/items/1")
+        .insert_header((header::IF_MATCH, etag.clone()))
+        .set_json(Item::new(1, "second".to_string()))
+        .to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), 412);
+    let req = TestRequest::patch()
+        .uri("/items/1")
+        .insert_header((header::IF_MATCH, etag))
+        .set_json(serde_json::json!({"name": "third"}))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 412);
+    assert_eq!(state.items.read().unwrap()[0].name, "first");
+
+    let req = TestRequest::patch()
+        .uri("/items/1")
+        .insert_header((header::IF_MATCH, "*"))
+        .set_json(serde_json::json!({"name": "any"}))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 200);
+}
+
+#[actix_web::test]
+async fn export_streams_one_json_object_per_line() {
+    let state = web::Data::new(test_state(items(1..=3)));
+    let app = app!(state);
+
+    let resp = call_service(&app, TestRequest::get().uri("/items/export").to_request()).await;
+    assert_eq!(resp.headers().get(header::CONTENT_TYPE).unwrap(), "application/x-ndjson");
+    let body = read_body(resp).await;
+    let body = std::str::from_utf8(&body).unwrap();
+    assert!(body.ends_with('\n'));
+    let exported: Vec<Item> = body.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+    assert_eq!(exported.iter().map(|i| i.id).collect::<Vec<_>>(), [1, 2, 3]);
+}
+
+#[actix_web::test]
+async fn csv_quotes_names_with_commas() {
+    assert_eq!(csv_field("plain"), "plain");
+    assert_eq!(csv_field("a,b"), "\"a,b\"");
+    assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+    assert_eq!(csv_field("two\nlines"), "\"two\nlines\"");
+
+    let state = web::Data::new(test_state(vec![Item::new(1, "apples, pears".to_string())]));
+    let app = app!(state);
+    let resp = call_service(&app, TestRequest::get().uri("/items.csv").to_request()).await;
+    assert_eq!(resp.headers().get(header::CONTENT_TYPE).unwrap(), "text/csv; charset=utf-8");
+    assert_eq!(resp.headers().get(header::CONTENT_DISPOSITION).unwrap(), "attachment; filename=items.csv");
+    assert_eq!(read_body(resp).await, "id,name\r\n1,\"apples, pears\"\r\n");
+}
+
+#[test]
+fn server_config_is_read_from_env() {
+    let _env = env_lock();
+    std::env::set_var("HOST", "0.0.0.0");
+    std::env::set_var("PORT", "9090");
+    std::env::set_var("PERSIST", "false");
+    std::env::set_var("MAX_ITEMS", "10");
+    std::env::set_var("REQUEST_TIMEOUT_MS", "0");
+    let config = ServerConfig::from_env().unwrap();
+    assert_eq!(config.bind_address(), "0.0.0.0:9090");
+    assert!(!config.persist);
+    assert_eq!(config.max_items, Some(10));
+    assert_eq!(config.request_timeout, None);
+    assert_eq!(config.max_body_bytes, 64 * 1024); // 未設定時使用預設值
+
+    std::env::set_var("PORT", "not-a-port");
+    let error = ServerConfig::from_env().err().unwrap();
+    assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+    assert!(error.to_string().contains("PORT"));
+    std::env::set_var("PORT", "9090");
+    std::env::set_var("WORKERS", "0");
+    assert!(ServerConfig::from_env().is_err());
+    std::env::set_var("WORKERS", "2");
+    assert_eq!(ServerConfig::from_env().unwrap().workers, Some(2));
+    std::env::remove_var("WORKERS");
+
+    std::env::remove_var("HOST");
+    std::env::remove_var("PORT");
+    std::env::remove_var("PERSIST");
+    std::env::remove_var("MAX_ITEMS");
+    std::env::remove_var("REQUEST_TIMEOUT_MS");
+    assert_eq!(ServerConfig::from_env().unwrap().bind_address(), "127.0.0.1:8080");
+}
+
+#[actix_web::test]
+async fn count_reflects_creates_and_deletes() {
+    let state = web::Data::new(test_state(vec![]));
+    let app = app!(state);
+    let count = |body: serde_json::Value| body["count"].as_u64().unwrap();
+
+    assert_eq!(count(call_and_read_body_json(&app, TestRequest::get().uri("/items/count").to_request()).await), 0);
+    for name in ["a", "b", "c"] {
+        let req = post_json("/items", serde_json::json!({ "name": name })).to_request();
+        assert_eq!(call_service(&app, req).await.status(), 201);
+    }
+    assert_eq!(count(call_and_read_body_json(&app, TestRequest::get().uri("/items/count").to_request()).await), 3);
+    assert_eq!(call_service(&app, TestRequest::delete().uri("/items/2").to_request()).await.status(), 200);
+    assert_eq!(count(call_and_read_body_json(&app, TestRequest::get().uri("/items/count").to_request()).await), 2);
+}
+
+#[actix_web::test]
+async fn concurrent_reads_are_served() {
+    let state = web::Data::new(test_state(items(1..=10)));
+    let app = app!(state);
+
+    let reads = (0..50).map(|n| call_service(&app, TestRequest::get().uri(&format!("/items/{}", n % 10 + 1)).to_request()));
+    for resp in futures_util::future::join_all(reads).await {
+        assert_eq!(resp.status(), 200);
+    }
+    assert_eq!(state.items.read().unwrap().len(), 10);
+}
+
+#[test]
+fn malformed_data_file_is_an_error() {
+    let dir = TempDir::new();
+    let path = dir.path("items.json");
+    fs::write(&path, "{not json").unwrap();
+    let error = load_items(&path).err().unwrap();
+    assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    assert!(error.to_string().contains(&path.display().to_string()));
+    assert_eq!(fs::read_to_string(&path).unwrap(), "{not json"); // 不覆蓋損壞的文件
+
+    assert!(load_items(&dir.path("missing.json")).unwrap().is_empty());
+}
+
+#[actix_web::test]
+async fn system_info_accepts_a_timezone() {
+    let app = init_service(App::new().service(get_system_info)).await;
+
+    let req = TestRequest::get().uri("/system_info?tz=UTC").to_request();
+    let info: serde_json::Value = call_and_read_body_json(&app, req).await;
+    assert_eq!(info["offset"], "+00:00");
+    assert!(DateTime::parse_from_rfc3339(info["utc"].as_str().unwrap()).is_ok());
+
+    let resp = call_service(&app, TestRequest::get().uri("/system_info?tz=Mars/Olympus").to_request()).await;
+    assert_eq!(resp.status(), 400);
+}
+
+#[actix_web::test]
+async fn soft_deleted_items_can_be_restored() {
+    let state = web::Data::new(test_state(items(1..=3)));
+    let app = app!(state);
+
+    let resp = call_service(&app, TestRequest::delete().uri("/items/2").to_request()).await;
+    assert_eq!(resp.status(), 200);
+    assert_eq!(state.items.read().unwrap().len(), 3); // 仍保留在列表中
+    let listed: Vec<Item> = call_and_read_body_json(&app, TestRequest::get().uri("/items").to_request()).await;
+    assert_eq!(listed.iter().map(|i| i.id).collect::<Vec<_>>(), [1, 3]);
+    let listed: Vec<Item> = call_and_read_body_json(&app, TestRequest::get().uri("/items?include_deleted=true").to_request()).await;
+    assert!(listed.iter().find(|i| i.id == 2).unwrap().deleted);
+    let resp = call_service(&app, TestRequest::delete().uri("/items/2").to_request()).await;
+    assert_eq!(resp.status(), 404);
+
+    let restored: Item = call_and_read_body_json(&app, TestRequest::post().uri("/items/2/restore").to_request()).await;
+    assert!(!restored.deleted);
+    let listed: Vec<Item> = call_and_read_body_json(&app, TestRequest::get().uri("/items").to_request()).await;
+    assert_eq!(listed.iter().map(|i| i.id).collect::<Vec<_>>(), [1, 2, 3]);
+    let resp = call_service(&app, TestRequest::post().uri("/items/9/restore").to_request()).await;
+    assert_eq!(resp.status(), 404);
+}
+
+#[actix_web::test]
+async fn oversized_body_returns_413() {
+    let state = web::Data::new(test_state(vec![]));
+    let app = app!(state);
+
+    let req = post_json("/items", serde_json::json!({"name": "x".repeat(70 * 1024)})).to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), 413);
+    let body: serde_json::Value = serde_json::from_slice(&read_body(resp).await).unwrap();
+    assert_eq!(body["code"], "payload_too_large");
+    assert!(state.items.read().unwrap().is_empty());
+}
+
+#[actix_web::test]
+async fn name_filter_is_an_exact_match() {
+    let stored = vec![Item::new(1, "apple".to_string()), Item::new(2, "apple pie".to_string()), Item::new(3, "Apple".to_string())];
+    let state = web::Data::new(test_state(stored));
+    let app = app!(state);
+
+    let listed: Vec<Item> = call_and_read_body_json(&app, TestRequest::get().uri("/items?name=apple").to_request()).await;
+    assert_eq!(ids(&listed), [1]);
+    let listed: Vec<Item> = call_and_read_body_json(&app, TestRequest::get().uri("/items?name=pear").to_request()).await;
+    assert!(listed.is_empty());
+}
+
+#[actix_web::test]
+async fn unchanged_list_returns_304() {
+    let state = web::Data::new(test_state(items([1])));
+    let app = app!(state);
+
+    let resp = call_service(&app, TestRequest::get().uri("/items").to_request()).await;
+    let last_modified = resp.headers().get(header::LAST_MODIFIED).unwrap().clone();
+    let req = TestRequest::get().uri("/items").insert_header((header::IF_MODIFIED_SINCE, last_modified.clone())).to_request();
+    assert_eq!(call_service(&app, req).await.status(), 304);
+
+    state.last_modified.fetch_add(5, Ordering::AcqRel); // 之後發生變更
+    let req = TestRequest::get().uri("/items").insert_header((header::IF_MODIFIED_SINCE, last_modified)).to_request();
+    assert_eq!(call_service(&app, req).await.status(), 200);
+}
+
+#[actix_web::test]
+async fn bulk_rename_changes_only_matching_items() {
+    let dir = TempDir::new();
+    let path = dir.path("items.json");
+    let stored = vec![Item::new(1, "old-a".to_string()), Item::new(2, "keep".to_string()), Item::new(3, "old-b".to_string())];
+    let state = web::Data::new(state_with_file(stored, path.clone(), true));
+    let app = app!(state);
+
+    let req = post_json("/items/bulk-rename", serde_json::json!({"prefix": "old-", "replacement": "new-"})).to_request();
+    let count: serde_json::Value = call_and_read_body_json(&app, req).await;
+    assert_eq!(count["count"], 2);
+    let names = |items: &[Item]| items.iter().map(|i| i.name.clone()).collect::<Vec<_>>();
+    assert_eq!(names(&state.items.read().unwrap()), ["new-a", "keep", "new-b"]);
+
+    let req = post_json("/items/bulk-rename", serde_json::json!({"prefix": "", "replacement": "x"})).to_request();
+    assert_eq!(call_service(&app, req).await.status(), 400);
+
+    assert!(!path.exists());
+    state.flush_if_dirty().unwrap(); // 所有修改一次寫入
+    assert_eq!(names(&saved_items(&path)), ["new-a", "keep", "new-b"]);
+}
+
+#[test]
+fn dumped_openapi_parses_back() {
+    let _env = env_lock();
+    let dir = TempDir::new();
+    let path = dir.path("openapi.json");
+    dump_openapi(&path).unwrap();
+    let spec: utoipa::openapi::OpenApi = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+    assert!(spec.paths.paths.contains_key("/items"));
+    assert!(spec.paths.paths.contains_key("/items/{id}"));
+}
+
+#[actix_web::test]
+async fn fields_selects_the_returned_keys() {
+    let state = web::Data::new(test_state(items(1..=2)));
+    let app = app!(state);
+
+    let listed: Vec<serde_json::Value> = call_and_read_body_json(&app, TestRequest::get().uri("/items?fields=id").to_request()).await;
+    assert_eq!(listed, [serde_json::json!({"id": 1}), serde_json::json!({"id": 2})]);
+    let listed: Vec<serde_json::Value> = call_and_read_body_json(&app, TestRequest::get().uri("/items?fields=id,name").to_request()).await;
+    assert_eq!(listed[0], serde_json::json!({"id": 1, "name": "item-1"}));
+    let resp = call_service(&app, TestRequest::get().uri("/items?fields=id,secret").to_request()).await;
+    assert_eq!(resp.status(), 400);
+}
+
+#[actix_web::test]
+async fn bulk_delete_removes_the_listed_items() {
+    let state = web::Data::new(test_state(items(1..=3)));
+    let app = app!(state);
+
+    let req = post_json("/items/bulk-delete", serde_json::json!({"ids": [1, 3, 99]})).to_request();
+    let count: serde_json::Value = call_and_read_body_json(&app, req).await;
+    assert_eq!(count["count"], 2); // 不存在的 ID 被忽略
+    let listed: Vec<Item> = call_and_read_body_json(&app, TestRequest::get().uri("/items").to_request()).await;
+    assert_eq!(ids(&listed), [2]);
+}
+
+#[actix_web::test]
+async fn events_stream_reports_created_items() {
+    let state = web::Data::new(test_state(vec![]));
+    let app = app!(state);
+
+    let resp = call_service(&app, TestRequest::get().uri("/items/events").to_request()).await;
+    assert_eq!(resp.headers().get(header::CONTENT_TYPE).unwrap(), "text/event-stream");
+    let mut body = resp.into_body();
+    let req = post_json("/items", serde_json::json!({"name": "watched"})).to_request();
+    assert_eq!(call_service(&app, req).await.status(), 201);
+
+    let frame = futures_util::future::poll_fn(|cx| std::pin::Pin::new(&mut body).poll_next(cx)).await.unwrap().unwrap();
+    assert_eq!(frame, "data: {\"type\":\"created\",\"id\":1}\n\n");
+}
+
+#[actix_web::test]
+async fn responses_are_compressed_when_accepted() {
+    let state = web::Data::new(test_state(items(1..=3)));
+    let app = init_service(App::new().wrap(Compress::default()).app_data(state.clone()).service(get_items).service(item_events)).await;
+
+    let req = TestRequest::get().uri("/items").insert_header((header::ACCEPT_ENCODING, "gzip")).to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.headers().get(header::CONTENT_ENCODING).unwrap(), "gzip");
+    let req = TestRequest::get().uri("/items/events").insert_header((header::ACCEPT_ENCODING, "gzip")).to_request();
+    let resp = call_service(&app, req).await;
+    assert_ne!(resp.headers().get(header::CONTENT_ENCODING).unwrap(), "gzip"); // 事件串流不壓縮
+}
+
+#[actix_web::test]
+async fn idempotency_key_creates_only_once() {
+    let state = web::Data::new(test_state(vec![]));
+    let app = app!(state);
+
+    let mut created = vec![];
+    for _ in 0..2 {
+        let req = post_json("/items", serde_json::json!({"name": "once"})).insert_header(("Idempotency-Key", "abc")).to_request();
+        let resp = call_service(&app, req).await;
+        assert_eq!(resp.status(), 201);
+        created.push(serde_json::from_slice::<Item>(&read_body(resp).await).unwrap().id);
+    }
+    assert_eq!(created, [1, 1]);
+    assert_eq!(state.items.read().unwrap().len(), 1);
+
+    let req = post_json("/items", serde_json::json!({"name": "other"})).insert_header(("Idempotency-Key", "def")).to_request();
+    assert_eq!(call_service(&app, req).await.status(), 201);
+    assert_eq!(state.items.read().unwrap().len(), 2);
+}
+
+#[actix_web::test]
+async fn batch_skips_missing_ids() {
+    let state = web::Data::new(test_state(items(1..=3)));
+    let app = app!(state);
+
+    let found: Vec<Item> = call_and_read_body_json(&app, TestRequest::get().uri("/items/batch?ids=3,9,1").to_request()).await;
+    assert_eq!(ids(&found), [3, 1]); // 依請求的順序
+    let resp = call_service(&app, TestRequest::get().uri("/items/batch?ids=1,x").to_request()).await;
+    assert_eq!(resp.status(), 400);
+    let too_many = (1..=101).map(|id| id.to_string()).collect::<Vec<_>>().join(",");
+    let resp = call_service(&app, TestRequest::get().uri(&format!("/items/batch?ids={}", too_many)).to_request()).await;
+    assert_eq!(resp.status(), 400);
+}
+
+#[test]
+fn saved_file_is_pretty_and_round_trips() {
+    let dir = TempDir::new();
+    let path = dir.path("items.json");
+    let mut stored = items(1..=2);
+    stored[1].tags = vec!["red".to_string()];
+    save_items(&path, &stored, 0).unwrap();
+
+    let contents = fs::read_to_string(&path).unwrap();
+    assert!(contents.lines().count() > 2);
+    let saved = saved_items(&path);
+    assert_eq!(serialize_data_file(&saved).unwrap(), contents);
+    assert_eq!(saved[1].tags, ["red"]);
+}
+
+#[actix_web::test]
+async fn list_filters_by_creation_time() {
+    let at = |time: &str| DateTime::parse_from_rfc3339(time).unwrap().with_timezone(&Local);
+    let stored = ["2024-01-01T00:00:00Z", "2024-01-02T12:00:00Z", "2024-01-04T00:00:00Z"]
+        .iter()
+        .enumerate()
+        .map(|(n, time)| Item { created_at: at(time), ..Item::new(n + 1, format!("item-{}", n + 1)) })
+        .collect();
+    let state = web::Data::new(test_state(stored));
+    let app = app!(state);
+
+    let req = TestRequest::get().uri("/items?from=2024-01-02T00:00:00Z&to=2024-01-03T00:00:00Z").to_request();
+    let listed: Vec<Item> = call_and_read_body_json(&app, req).await;
+    assert_eq!(ids(&listed), [2]);
+    let req = TestRequest::get().uri("/items?from=2024-01-02T12:00:00Z").to_request();
+    let listed: Vec<Item> = call_and_read_body_json(&app, req).await;
+    assert_eq!(ids(&listed), [2, 3]); // 包含邊界時間
+
+    let req = TestRequest::get().uri("/items?from=2024-01-03T00:00:00Z&to=2024-01-02T00:00:00Z").to_request();
+    assert_eq!(call_service(&app, req).await.status(), 400);
+    assert_eq!(call_service(&app, TestRequest::get().uri("/items?from=yesterday").to_request()).await.status(), 400);
+}
+
+#[actix_web::test]
+async fn version_matches_the_package() {
+    let app = init_service(App::new().service(version)).await;
+    let info: serde_json::Value = call_and_read_body_json(&app, TestRequest::get().uri("/version").to_request()).await;
+    assert_eq!(info["version"], env!("CARGO_PKG_VERSION"));
+}
+
+#[test]
+fn merge_patch_removes_null_fields_and_keeps_omitted_ones() {
+    let mut target = serde_json::json!({"name": "a", "parent_id": 1, "tags": ["x"]});
+    merge_patch(&mut target, &serde_json::json!({"name": "b"}));
+    assert_eq!(target, serde_json::json!({"name": "b", "parent_id": 1, "tags": ["x"]}));
+    merge_patch(&mut target, &serde_json::json!({"parent_id": null}));
+    assert_eq!(target, serde_json::json!({"name": "b", "tags": ["x"]}));
+}
+
+#[actix_web::test]
+async fn patch_distinguishes_null_from_omitted_fields() {
+    let state = web::Data::new(test_state(vec![Item::new(1, "parent".to_string()), child(2, 1)]));
+    let app = app!(state);
+    let parent_id = if cfg!(feature = "camel-case") { "parentId" } else { "parent_id" };
+
+    let req = TestRequest::patch().uri("/items/2").set_json(serde_json::json!({"name": "renamed"})).to_request();
+    let patched: Item = call_and_read_body_json(&app, req).await;
+    assert_eq!(patched.parent_id, Some(1)); // 未提供的欄位保持不變
+    let req = TestRequest::patch().uri("/items/2").set_json(serde_json::json!({ parent_id: null })).to_request();
+    let patched: Item = call_and_read_body_json(&app, req).await;
+    assert_eq!(patched.parent_id, None); // 明確設為 null 時清除
+    assert_eq!(patched.name, "renamed");
+}
+
+#[test]
+fn env_vars_are_parsed_with_defaults() {
+    let _env = env_lock();
+    std::env::set_var("RUST_API_TEST_VALUE", "42");
+    assert_eq!(parse_env_var("RUST_API_TEST_VALUE", 7u16).unwrap(), 42);
+    assert_eq!(parse_optional_env_var::<u16>("RUST_API_TEST_VALUE").unwrap(), Some(42));
+    std::env::set_var("RUST_API_TEST_VALUE", "forty-two");
+    let error = parse_env_var("RUST_API_TEST_VALUE", 7u16).unwrap_err();
+    assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+    assert!(error.to_string().contains("RUST_API_TEST_VALUE"));
+    std::env::remove_var("RUST_API_TEST_VALUE");
+    assert_eq!(parse_env_var("RUST_API_TEST_VALUE", 7u16).unwrap(), 7);
+    assert_eq!(parse_optional_env_var::<u16>("RUST_API_TEST_VALUE").unwrap(), None);
+}
+
+#[actix_web::test]
+async fn create_returns_201_with_location() {
+    let state = web::Data::new(test_state(items([1])));
+    let app = app!(state);
+
+    let req = post_json("/items", serde_json::json!({"name": "new"})).to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), 201);
+    let location = resp.headers().get(header::LOCATION).unwrap().to_str().unwrap().to_string();
+    let created: Item = serde_json::from_slice(&read_body(resp).await).unwrap();
+    assert_eq!(location, format!("/items/{}", created.id));
+    let resp = call_service(&app, TestRequest::get().uri(&location).to_request()).await;
+    assert_eq!(resp.status(), 200);
+
+    let req = TestRequest::put().uri("/items/1").set_json(Item::new(1, "changed".to_string())).to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), 200); // 更新現有項目時為 200
+    assert!(!resp.headers().contains_key(header::LOCATION));
+}
+
+#[actix_web::test]
+async fn list_filters_by_tag() {
+    let mut stored = items(1..=3);
+    stored[0].tags = vec!["red".to_string()];
+    stored[2].tags = vec!["blue".to_string(), "red".to_string()];
+    let state = web::Data::new(test_state(stored));
+    let app = app!(state);
+
+    let listed: Vec<Item> = call_and_read_body_json(&app, TestRequest::get().uri("/items?tag=red").to_request()).await;
+    assert_eq!(ids(&listed), [1, 3]);
+    let req = post_json("/items", serde_json::json!({"name": "tagged", "tags": ["green"]})).to_request();
+    let created: Item = call_and_read_body_json(&app, req).await;
+    assert_eq!(created.tags, ["green"]);
+}
+
+#[test]
+fn second_lock_on_the_data_file_fails() {
+    let dir = TempDir::new();
+    let path = dir.path("items.json");
+    let lock = lock_data_file(&path).unwrap();
+    let error = lock_data_file(&path).unwrap_err();
+    assert_eq!(error.kind(), io::ErrorKind::WouldBlock);
+    lock.unlock().unwrap();
+    assert!(lock_data_file(&path).is_ok()); // 釋放後可以再次取得
+}
+
+#[actix_web::test]
+async fn disabled_persistence_writes_no_file() {
+    let dir = TempDir::new();
+    let path = dir.path("items.json");
+    let state = web::Data::new(state_with_file(vec![], path.clone(), false));
+    let app = app!(state);
+
+    let req = post_json("/items", serde_json::json!({"name": "memory"})).to_request();
+    assert_eq!(call_service(&app, req).await.status(), 201);
+    state.flush_if_dirty().unwrap();
+    flush_items(&state).unwrap();
+    assert!(!path.exists());
+    assert_eq!(fs::read_dir(&dir.0).unwrap().count(), 0);
+}
+
+#[actix_web::test]
+async fn put_can_change_the_id() {
+    let state = web::Data::new(test_state(vec![Item::new(1, "parent".to_string()), Item::new(2, "other".to_string()), child(3, 1)]));
+    let app = app!(state);
+
+    let req = TestRequest::put().uri("/items/1").set_json(Item::new(10, "moved".to_string())).to_request();
+    assert_eq!(call_service(&app, req).await.status(), 200);
+    assert_eq!(call_service(&app, TestRequest::get().uri("/items/1").to_request()).await.status(), 404);
+    let moved: Item = call_and_read_body_json(&app, TestRequest::get().uri("/items/10").to_request()).await;
+    assert_eq!(moved.name, "moved");
+    let child: Item = call_and_read_body_json(&app, TestRequest::get().uri("/items/3").to_request()).await;
+    assert_eq!(child.parent_id, Some(10)); // 子項目跟著指向新的 ID
+
+    let req = TestRequest::put().uri("/items/10").set_json(Item::new(2, "collides".to_string())).to_request();
+    assert_eq!(call_service(&app, req).await.status(), 409);
+    let req = TestRequest::put().uri("/items/99").set_json(Item::new(100, "missing".to_string())).to_request();
+    assert_eq!(call_service(&app, req).await.status(), 404);
+    assert_eq!(ids(&state.items.read().unwrap()), [10, 2, 3]);
+}
+
+#[actix_web::test]
+async fn replace_import_loads_the_file() {
+    let state = web::Data::new(test_state(items([1])));
+    let app = app!(state);
+
+    let file = serde_json::to_string(&items([7, 8])).unwrap();
+    let count: serde_json::Value = call_and_read_body_json(&app, upload("/items/import?mode=replace", &file).to_request()).await;
+    assert_eq!(count["count"], 2);
+    let listed: Vec<Item> = call_and_read_body_json(&app, TestRequest::get().uri("/items").to_request()).await;
+    assert_eq!(ids(&listed), [7, 8]);
+
+    let file = serde_json::to_string(&vec![Item::new(8, "merged".to_string()), Item::new(9, "new".to_string())]).unwrap();
+    let count: serde_json::Value = call_and_read_body_json(&app, upload("/items/import", &file).to_request()).await;
+    assert_eq!(count["count"], 2);
+    let listed: Vec<Item> = call_and_read_body_json(&app, TestRequest::get().uri("/items").to_request()).await;
+    assert_eq!(listed.iter().map(|i| i.name.as_str()).collect::<Vec<_>>(), ["item-7", "merged", "new"]);
+
+    let resp = call_service(&app, upload("/items/import?mode=append", &file).to_request()).await;
+    assert_eq!(resp.status(), 400);
+    let resp = call_service(&app, upload("/items/import", "not json").to_request()).await;
+    assert_eq!(resp.status(), 400);
+}
+
+#[actix_web::test]
+async fn dry_run_delete_reports_without_changing() {
+    let state = web::Data::new(test_state(items(1..=3)));
+    let app = app!(state);
+    let dry_run = if cfg!(feature = "camel-case") { "dryRun" } else { "dry_run" };
+
+    let result: serde_json::Value = call_and_read_body_json(&app, TestRequest::delete().uri("/items/2?dry_run=true").to_request()).await;
+    assert_eq!(result[dry_run], true);
+    assert_eq!(result["count"], 1);
+    assert_eq!(result["items"][0]["id"], 2);
+    let req = post_json("/items/bulk-delete?dry_run=true", serde_json::json!({"ids": [1, 3, 9]})).to_request();
+    let result: serde_json::Value = call_and_read_body_json(&app, req).await;
+    assert_eq!(result["count"], 2);
+    let req = post_json("/items/bulk-rename?dry_run=true", serde_json::json!({"prefix": "item-", "replacement": "x-"})).to_request();
+    let result: serde_json::Value = call_and_read_body_json(&app, req).await;
+    assert_eq!(result["items"][0]["name"], "x-1"); // 返回修改後的內容
+
+    let items = state.items.read().unwrap();
+    assert!(items.iter().all(|i| !i.deleted && i.name.starts_with("item-")));
+    assert!(!state.dirty.load(Ordering::Acquire));
+}
+
+#[test]
+fn tls_needs_both_cert_and_key() {
+    assert!(resolve_tls(None, None).unwrap().is_none());
+    let paths = resolve_tls(Some("cert.pem".to_string()), Some("key.pem".to_string())).unwrap().unwrap();
+    assert_eq!(paths.cert, PathBuf::from("cert.pem"));
+    assert_eq!(paths.key, PathBuf::from("key.pem"));
+    assert!(resolve_tls(Some("cert.pem".to_string()), None).is_err());
+    assert!(resolve_tls(None, Some("key.pem".to_string())).is_err());
+}
+
+#[test]
+fn stats_for_a_known_dataset() {
+    let mut stored = vec![Item::new(1, "a".to_string()), Item::new(2, "abcd".to_string()), Item::new(3, "abc".to_string())];
+    stored[2].created_at = stored[0].created_at; // 創建時間相同時取較大的 ID
+    stored[1] = deleted(stored[1].clone());
+    let stats = ItemStats::from_items(&stored);
+    assert_eq!(stats.count, 2);
+    assert_eq!(stats.latest_id, Some(3));
+    assert_eq!(stats.longest_name, 3); // 不計算已刪除的項目
+    assert_eq!(stats.shortest_name, 1);
+
+    let empty = ItemStats::from_items(&[]);
+    assert_eq!((empty.count, empty.latest_id, empty.longest_name, empty.shortest_name), (0, None, 0, 0));
+}
+
+#[test]
+fn with_retries_recovers_from_one_failure() {
+    let mut attempts = 0;
+    let result = with_retries(1, || {
+        attempts += 1;
+        if attempts == 1 {
+            Err(io::Error::other("busy"))
+        } else {
+            Ok(())
+        }
+    });
+    assert!(result.is_ok());
+    assert_eq!(attempts, 2);
+
+    let mut attempts = 0;
+    let result = with_retries(2, || {
+        attempts += 1;
+        Err(io::Error::other("busy"))
+    });
+    assert!(result.is_err());
+    assert_eq!(attempts, 3);
+}
+
+#[actix_web::test]
+async fn paged_response_reports_has_more_at_the_boundary() {
+    let state = web::Data::new(test_state(items(1..=4)));
+    let app = app!(state);
+    let has_more = if cfg!(feature = "camel-case") { "hasMore" } else { "has_more" };
+
+    let req = TestRequest::get().uri("/items?paged=true&limit=2&offset=1").to_request();
+    let page: serde_json::Value = call_and_read_body_json(&app, req).await;
+    assert_eq!(page["total"], 4);
+    assert_eq!(page["items"].as_array().unwrap().len(), 2);
+    assert_eq!(page[has_more], true);
+
+    let req = TestRequest::get().uri("/items?paged=true&limit=2&offset=2").to_request();
+    let page: serde_json::Value = call_and_read_body_json(&app, req).await;
+    assert_eq!(page["items"].as_array().unwrap().len(), 2);
+    assert_eq!(page[has_more], false); // 剛好取到最後一個項目
+
+    let req = TestRequest::get().uri("/items").to_request();
+    let listed: serde_json::Value = call_and_read_body_json(&app, req).await;
+    assert!(listed.is_array()); // 未指定 paged 時仍返回陣列
+}
+
+#[actix_web::test]
+async fn unique_names_are_enforced_only_when_enabled() {
+    for unique_names in [false, true] {
+        let mut state = test_state(items([1]));
+        state.unique_names = unique_names;
+        let state = web::Data::new(state);
+        let app = app!(state);
+
+        let req = post_json("/items", serde_json::json!({"name": "item-1"})).to_request();
+        let expected = if unique_names { 409 } else { 201 };
+        assert_eq!(call_service(&app, req).await.status(), expected);
+        let req = TestRequest::put().uri("/items/1").set_json(Item::new(1, "item-1".to_string())).to_request();
+        assert_eq!(call_service(&app, req).await.status(), 200); // 保留自己的名稱不算重複
+
+        let file = serde_json::to_string(&[Item::new(3, "item-1".to_string())]).unwrap();
+        let expected = if unique_names { 409 } else { 200 };
+        assert_eq!(call_service(&app, upload("/items/import", &file).to_request()).await.status(), expected);
+    }
+}
+
+#[actix_web::test]
+async fn items_are_served_under_the_prefix() {
+    let mut state = test_state(items([1]));
+    state.api_prefix = "/api/v1".to_string();
+    let state = web::Data::new(state);
+    let app = init_service(
+        App::new()
+            .app_data(state.clone())
+            .service(ready)
+            .service(web::scope("/api/v1").configure(configure)),
+    )
+    .await;
+
+    assert_eq!(call_service(&app, TestRequest::get().uri("/api/v1/items").to_request()).await.status(), 200);
+    assert_eq!(call_service(&app, TestRequest::get().uri("/items").to_request()).await.status(), 404);
+    assert_eq!(call_service(&app, TestRequest::get().uri("/ready").to_request()).await.status(), 200); // 系統服務不受前綴影響
+    let req = post_json("/api/v1/items", serde_json::json!({"name": "new"})).to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.headers().get(header::LOCATION).unwrap(), "/api/v1/items/2");
+
+    let _env = env_lock();
+    std::env::set_var("API_PREFIX", "/api/v1");
+    let paths = api_doc(&api_prefix(), false, false).paths.paths;
+    std::env::remove_var("API_PREFIX");
+    assert!(paths.contains_key("/api/v1/items"));
+    assert!(!paths.contains_key("/items"));
+}
+
+#[test]
+fn seed_files_are_merged_in_name_order() {
+    let dir = TempDir::new();
+    fs::write(dir.path("b.json"), r#"{"name": "single"}"#).unwrap();
+    fs::write(dir.path("a.json"), r#"[{"id": 50, "name": "first"}, {"name": "second", "tags": ["x"]}]"#).unwrap();
+    fs::write(dir.path("c.json"), "not json").unwrap();
+    fs::write(dir.path("notes.txt"), r#"{"name": "ignored"}"#).unwrap();
+
+    let seeded = load_seed(&dir.0);
+    assert_eq!(seeded.iter().map(|i| (i.id, i.name.as_str())).collect::<Vec<_>>(), [(1, "first"), (2, "second"), (3, "single")]);
+    assert_eq!(seeded[1].tags, ["x"]);
+    assert!(load_seed(&dir.path("missing")).is_empty());
+}
+
+#[actix_web::test]
+async fn maintenance_rejects_writes_but_allows_reads() {
+    let state = web::Data::new(test_state(items([1])));
+    let mode = web::Data::new(MaintenanceMode::new(false));
+    let app = init_service(
+        App::new()
+            .wrap(Maintenance::new(mode.clone()))
+            .app_data(mode.clone())
+            .app_data(state.clone())
+            .service(set_maintenance)
+            .service(create_item)
+            .service(get_item),
+    )
+    .await;
+
+    let req = TestRequest::post().uri("/admin/maintenance").set_json(serde_json::json!({"enabled": true})).to_request();
+    assert_eq!(call_service(&app, req).await.status(), 200);
+    let req = TestRequest::post().uri("/items").set_json(serde_json::json!({"name": "new"})).to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), 503);
+    assert!(resp.headers().contains_key(header::RETRY_AFTER));
+    let resp = call_service(&app, TestRequest::get().uri("/items/1").to_request()).await;
+    assert_eq!(resp.status(), 200);
+
+    let req = TestRequest::post().uri("/admin/maintenance").set_json(serde_json::json!({"enabled": false})).to_request();
+    assert_eq!(call_service(&app, req).await.status(), 200); // 維護模式下仍可以關閉
+    let req = TestRequest::post().uri("/items").set_json(serde_json::json!({"name": "new"})).to_request();
+    assert_eq!(call_service(&app, req).await.status(), 201);
+}
+
+#[cfg(feature = "yaml")]
+#[actix_web::test]
+async fn yaml_is_returned_when_accepted() {
+    let state = web::Data::new(test_state(items(1..=2)));
+    let app = app!(state);
+
+    let req = TestRequest::get().uri("/items").insert_header((header::ACCEPT, "application/yaml")).to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.headers().get(header::CONTENT_TYPE).unwrap(), "application/yaml");
+    let listed: Vec<Item> = serde_yaml::from_slice(&read_body(resp).await).unwrap();
+    assert_eq!(ids(&listed), [1, 2]);
+}
+
+#[actix_web::test]
+async fn read_only_mode_allows_only_reads() {
+    let state = web::Data::new(test_state(items([1])));
+    let app = init_service(App::new().wrap(ReadOnly::new(true)).app_data(state.clone()).configure(configure)).await;
+
+    assert_eq!(call_service(&app, TestRequest::get().uri("/items").to_request()).await.status(), 200);
+    let req = post_json("/items", serde_json::json!({"name": "new"})).to_request();
+    assert_eq!(call_service(&app, req).await.status(), 405);
+    assert_eq!(call_service(&app, TestRequest::delete().uri("/items/1").to_request()).await.status(), 405);
+    assert_eq!(state.items.read().unwrap().len(), 1);
+}
+
+#[test]
+fn read_only_docs_list_only_reads() {
+    let openapi = api_doc("", true, false);
+    let items = &openapi.paths.paths["/items"];
+    assert!(items.operations.contains_key(&utoipa::openapi::PathItemType::Get));
+    assert!(!items.operations.contains_key(&utoipa::openapi::PathItemType::Post));
+    assert!(!openapi.paths.paths.contains_key("/items/bulk-delete"));
+}
+
+#[actix_web::test]
+async fn expected_version_detects_concurrent_updates() {
+    let state = web::Data::new(test_state(items([1])));
+    let app = app!(state);
+
+    let req = TestRequest::put().uri("/items/1?expected_version=1").set_json(Item::new(1, "first".to_string())).to_request();
+    assert_eq!(call_service(&app, req).await.status(), 200);
+    let updated: Item = call_and_read_body_json(&app, TestRequest::get().uri("/items/1").to_request()).await;
+    assert_eq!(updated.version, 2);
+    let req = TestRequest::put().uri("/items/1?expected_version=1").set_json(Item::new(1, "stale".to_string())).to_request();
+    assert_eq!(call_service(&app, req).await.status(), 409);
+    assert_eq!(state.items.read().unwrap()[0].name, "first");
+
+    let req = TestRequest::patch().uri("/items/1").set_json(serde_json::json!({"version": 99})).to_request();
+    let patched: Item = call_and_read_body_json(&app, req).await;
+    assert_eq!(patched.version, 3); // 版本號由伺服器管理
+}
+
+#[actix_web::test]
+async fn rejected_writes_do_not_consume_versions() {
+    let state = web::Data::new(test_state(items(1..=2)));
+    let app = app!(state);
+    let before = state.change_version.load(Ordering::Acquire);
+
+    let req = post_json("/items", serde_json::json!({"name": "orphan", "parent_id": 99})).to_request();
+    assert_eq!(call_service(&app, req).await.status(), 400);
+    let req = post_json("/items/bulk", serde_json::json!([{"name": "a"}, {"name": " "}])).to_request();
+    assert_eq!(call_service(&app, req).await.status(), 400);
+    let req = TestRequest::patch().uri("/items/1").set_json(serde_json::json!({"name": ""})).to_request();
+    assert_eq!(call_service(&app, req).await.status(), 400);
+    assert_eq!(state.change_version.load(Ordering::Acquire), before); // 被拒絕的請求不遞增版本號
+
+    let req = post_json("/items", serde_json::json!({"name": "third"})).to_request();
+    let created: Item = call_and_read_body_json(&app, req).await;
+    assert_eq!(created.version, before + 1);
+}
+
+#[actix_web::test]
+async fn checksum_changes_only_with_the_items() {
+    let state = web::Data::new(test_state(items(1..=2)));
+    let app = app!(state);
+    let checksum = || TestRequest::get().uri("/items/checksum").to_request();
+
+    let first: serde_json::Value = call_and_read_body_json(&app, checksum()).await;
+    let second: serde_json::Value = call_and_read_body_json(&app, checksum()).await;
+    assert_eq!(first, second);
+    assert_eq!(first["count"], 2);
+    let expected = format!("{:x}", Sha256::digest(serialize_data_file(&state.read_items().unwrap()).unwrap().as_bytes()));
+    assert_eq!(first["sha256"], expected);
+
+    let req = post_json("/items", serde_json::json!({"name": "new"})).to_request();
+    assert_eq!(call_service(&app, req).await.status(), 201);
+    let third: serde_json::Value = call_and_read_body_json(&app, checksum()).await;
+    assert_ne!(third["sha256"], first["sha256"]);
+}
+
+#[actix_web::test]
+async fn parent_links_are_validated() {
+    let state = web::Data::new(test_state(items([1])));
+    let app = app!(state);
+
+    let req = post_json("/items", serde_json::json!({"name": "child", "parent_id": 1})).to_request();
+    assert_eq!(call_service(&app, req).await.status(), 201);
+    let req = post_json("/items", serde_json::json!({"name": "grandchild", "parent_id": 2})).to_request();
+    assert_eq!(call_service(&app, req).await.status(), 201);
+    let children: Vec<Item> = call_and_read_body_json(&app, TestRequest::get().uri("/items/1/children").to_request()).await;
+    assert_eq!(ids(&children), [2]); // 只返回直接子項目
+
+    let req = post_json("/items", serde_json::json!({"name": "orphan", "parent_id": 99})).to_request();
+    assert_eq!(call_service(&app, req).await.status(), 400);
+    let req = TestRequest::put().uri("/items/1").set_json(child(1, 3)).to_request(); // 1 -> 3 -> 2 -> 1
+    assert_eq!(call_service(&app, req).await.status(), 400);
+    let req = TestRequest::put().uri("/items/1").set_json(child(1, 1)).to_request();
+    assert_eq!(call_service(&app, req).await.status(), 400);
+}
+
+#[test]
+fn backups_are_rotated() {
+    let dir = TempDir::new();
+    let path = dir.path("items.json");
+    for n in 1..=5 {
+        save_items(&path, &items(1..=n), 2).unwrap();
+        std::thread::sleep(Duration::from_millis(5)); // 備份名稱的時間精確到毫秒
+    }
+    let mut backups: Vec<String> = fs::read_dir(&dir.0)
+        .unwrap()
+        .filter_map(|entry| entry.unwrap().file_name().into_string().ok())
+        .filter(|name| name.starts_with("items.json.bak."))
+        .collect();
+    backups.sort();
+    assert_eq!(backups.len(), 2);
+    assert_eq!(saved_items(&dir.path(&backups[1])).len(), 4); // 最新的備份為上一次寫入的內容
+    assert_eq!(saved_items(&path).len(), 5);
+}
+
+#[actix_web::test]
+async fn json_patch_replaces_the_name() {
+    let state = web::Data::new(test_state(items([1])));
+    let app = app!(state);
+    let json_patch = |body: &str| {
+        TestRequest::patch()
+            .uri("/items/1")
+            .insert_header((header::CONTENT_TYPE, "application/json-patch+json"))
+            .set_payload(body.to_string())
+            .to_request()
+    };
+
+    let patched: Item = call_and_read_body_json(&app, json_patch(r#"[{"op": "replace", "path": "/name", "value": "patched"}]"#)).await;
+    assert_eq!(patched.name, "patched");
+    let resp = call_service(&app, json_patch(r#"[{"op": "test", "path": "/name", "value": "other"}]"#)).await;
+    assert_eq!(resp.status(), 400);
+    let resp = call_service(&app, json_patch(r#"[{"op": "remove", "path": "/name"}]"#)).await;
+    assert_eq!(resp.status(), 400); // 結果不是有效的項目
+    assert_eq!(state.read_items().unwrap()[0].name, "patched");
+}
+
+#[actix_web::test]
+async fn random_item_is_a_member() {
+    let state = web::Data::new(test_state(vec![]));
+    let app = app!(state);
+    assert_eq!(call_service(&app, TestRequest::get().uri("/items/random").to_request()).await.status(), 404);
+
+    let state = web::Data::new(test_state(items(1..=3)));
+    let app = app!(state);
+    for _ in 0..10 {
+        let item: Item = call_and_read_body_json(&app, TestRequest::get().uri("/items/random").to_request()).await;
+        assert!((1..=3).contains(&item.id));
+    }
+}
+
+#[actix_web::test]
+async fn reload_replaces_the_items_with_the_file() {
+    let dir = TempDir::new();
+    let path = dir.path("items.json");
+    let state = web::Data::new(state_with_file(items([1]), path.clone(), true));
+    let app = app!(state);
+
+    save_items(&path, &items(1..=3), 0).unwrap(); // 由其他程式修改的文件
+    let count: serde_json::Value = call_and_read_body_json(&app, TestRequest::post().uri("/admin/reload").to_request()).await;
+    assert_eq!(count["count"], 3);
+    assert_eq!(ids(&state.read_items().unwrap()), [1, 2, 3]);
+
+    fs::write(&path, "{not json").unwrap();
+    let resp = call_service(&app, TestRequest::post().uri("/admin/reload").to_request()).await;
+    assert_eq!(resp.status(), 500);
+    assert_eq!(state.read_items().unwrap().len(), 3); // 載入失敗時保留目前的項目
+
+    let state = web::Data::new(test_state(items([1])));
+    let app = app!(state);
+    let resp = call_service(&app, TestRequest::post().uri("/admin/reload").to_request()).await;
+    assert_eq!(resp.status(), 409);
+}
+
+// 以實際的 HTTP 連線送出請求並返回完整的響應文字，HEAD 的響應內容由 HTTP 層移除，只能以實際連線確認
+async fn raw_http(state: web::Data<AppState>, request: &'static str) -> String {
+    let server = HttpServer::new(move || App::new().app_data(state.clone()).configure(configure))
+        .workers(1)
+        .bind(("127.0.0.1", 0))
+        .unwrap();
+    let addr = server.addrs()[0];
+    let server = server.run();
+    let handle = server.handle();
+    actix_web::rt::spawn(server);
+    let response = web::block(move || {
+        let mut stream = std::net::TcpStream::connect(addr).unwrap();
+        stream.write_all(request.as_bytes()).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    })
+    .await
+    .unwrap();
+    handle.stop(false).await;
+    response
+}
+
+#[actix_web::test]
+async fn head_returns_headers_without_a_body() {
+    let state = web::Data::new(test_state(items(1..=2)));
+
+    let response = raw_http(state.clone(), "HEAD /items HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n").await;
+    let (head, body) = response.split_once("\r\n\r\n").unwrap();
+    assert!(head.starts_with("HTTP/1.1 200"));
+    assert!(head.to_lowercase().contains("x-total-count: 2"));
+    assert!(body.is_empty());
+
+    let response = raw_http(state.clone(), "HEAD /items/1 HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n").await;
+    let (head, body) = response.split_once("\r\n\r\n").unwrap();
+    assert!(head.to_lowercase().contains("etag: "));
+    assert!(body.is_empty());
+
+    let app = app!(state);
+    let resp = call_service(&app, TestRequest::default().method(Method::HEAD).uri("/items/9").to_request()).await;
+    assert_eq!(resp.status(), 404);
+}
+
+#[actix_web::test]
+async fn ndjson_import_reports_malformed_lines() {
+    let state = web::Data::new(test_state(vec![]));
+    let app = app!(state);
+    let failed_lines = if cfg!(feature = "camel-case") { "failedLines" } else { "failed_lines" };
+
+    let req = TestRequest::post()
+        .uri("/items/import-ndjson")
+        .insert_header((header::CONTENT_TYPE, "application/x-ndjson"))
+        .set_payload("{\"name\": \"a\"}\n{\"name\": \n{\"name\": \"c\"}")
+        .to_request();
+    let result: serde_json::Value = call_and_read_body_json(&app, req).await;
+    assert_eq!(result["imported"], 2);
+    assert_eq!(result[failed_lines], serde_json::json!([2]));
+    assert_eq!(state.read_items().unwrap().iter().map(|i| i.name.as_str()).collect::<Vec<_>>(), ["a", "c"]);
+}
+
+#[actix_web::test]
+async fn ndjson_import_is_applied_in_batches() {
+    let state = web::Data::new(test_state(vec![]));
+    let app = app!(state);
+
+    let mut body: String = (1..NDJSON_BATCH_LINES * 2).map(|n| format!("{{\"id\": {}, \"name\": \"item-{}\"}}\n", n, n)).collect();
+    body.push_str("{\"name\": \"child\", \"parent_id\": 1}\n"); // 父項目位於較早的一批
+    let req = TestRequest::post()
+        .uri("/items/import-ndjson")
+        .insert_header((header::CONTENT_TYPE, "application/x-ndjson"))
+        .set_payload(body)
+        .to_request();
+    let result: serde_json::Value = call_and_read_body_json(&app, req).await;
+    assert_eq!(result["imported"], NDJSON_BATCH_LINES * 2);
+    let items = state.read_items().unwrap();
+    assert_eq!(items.len(), NDJSON_BATCH_LINES * 2);
+    assert_eq!(items[NDJSON_BATCH_LINES * 2 - 1].id, NDJSON_BATCH_LINES * 2); // 跨批次仍依序分配 ID
+    assert_eq!(items[NDJSON_BATCH_LINES * 2 - 1].parent_id, Some(1));
+}
+
+#[actix_web::test]
+async fn changes_since_a_cursor() {
+    let state = web::Data::new(test_state(vec![]));
+    let app = app!(state);
+    for name in ["a", "b", "c"] {
+        let req = post_json("/items", serde_json::json!({ "name": name })).to_request();
+        assert_eq!(call_service(&app, req).await.status(), 201);
+    }
+
+    let changes: serde_json::Value = call_and_read_body_json(&app, TestRequest::get().uri("/items/changes").to_request()).await;
+    assert_eq!(changes["items"].as_array().unwrap().len(), 3);
+    let cursor = changes["version"].as_u64().unwrap();
+    let req = TestRequest::patch().uri("/items/2").set_json(serde_json::json!({"name": "b2"})).to_request();
+    assert_eq!(call_service(&app, req).await.status(), 200);
+
+    let req = TestRequest::get().uri(&format!("/items/changes?since={}", cursor)).to_request();
+    let changes: serde_json::Value = call_and_read_body_json(&app, req).await;
+    let changed: Vec<Item> = serde_json::from_value(changes["items"].clone()).unwrap();
+    assert_eq!(ids(&changed), [2]);
+    assert!(changes["version"].as_u64().unwrap() > cursor);
+}
+
+#[test]
+fn field_names_follow_the_naming_feature() {
+    let value = serde_json::to_value(child(2, 1)).unwrap();
+    let keys: Vec<&str> = value.as_object().unwrap().keys().map(String::as_str).collect();
+    let mut expected = ITEM_FIELDS.to_vec();
+    expected.sort_unstable();
+    let mut keys = keys;
+    keys.sort_unstable();
+    assert_eq!(keys, expected);
+    if cfg!(feature = "camel-case") {
+        assert_eq!(value["parentId"], 1);
+        assert!(value.get("created_at").is_none());
+    } else {
+        assert_eq!(value["parent_id"], 1);
+        assert!(value.get("createdAt").is_none());
+    }
+    let parsed: Item = serde_json::from_value(serde_json::json!({"id": 1, "name": "a", "parent_id": 2})).unwrap();
+    assert_eq!(parsed.parent_id, Some(2)); // 兩種設定都能讀取 snake_case 的資料文件
+}
+
+#[actix_web::test]
+async fn default_tags_apply_to_untagged_items() {
+    let mut state = test_state(vec![]);
+    state.defaults = ItemDefaults { tags: vec!["inbox".to_string()], name_prefix: Some("item-".to_string()) };
+    let state = web::Data::new(state);
+    let app = app!(state);
+
+    let created: Item = call_and_read_body_json(&app, post_json("/items", serde_json::json!({"name": "plain"})).to_request()).await;
+    assert_eq!(created.tags, ["inbox"]);
+    let req = post_json("/items", serde_json::json!({"name": "tagged", "tags": ["work"]})).to_request();
+    let created: Item = call_and_read_body_json(&app, req).await;
+    assert_eq!(created.tags, ["work"]); // 只填入省略的欄位
+    let created: Item = call_and_read_body_json(&app, post_json("/items", serde_json::json!({})).to_request()).await;
+    assert_eq!(created.name, "item-3");
+}
+
+#[actix_web::test]
+async fn audit_log_records_each_change() {
+    let dir = TempDir::new();
+    let path = dir.path("audit.log");
+    let mut state = test_state(vec![]);
+    state.audit_log = Some(AuditLog::open(&path).unwrap());
+    let state = web::Data::new(state);
+    let app = app!(state);
+
+    let req = post_json("/items", serde_json::json!({"name": "audited"})).to_request();
+    assert_eq!(call_service(&app, req).await.status(), 201);
+    assert_eq!(call_service(&app, TestRequest::delete().uri("/items/1").to_request()).await.status(), 200);
+
+    let log = fs::read_to_string(&path).unwrap();
+    let entries: Vec<serde_json::Value> = log.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0]["method"], "POST");
+    assert_eq!(entries[0]["id"], 1);
+    assert_eq!(entries[0]["before"], serde_json::Value::Null);
+    assert_eq!(entries[0]["after"], "audited");
+    assert_eq!(entries[1]["method"], "DELETE");
+    assert_eq!(entries[1]["before"], "audited");
+    assert_eq!(entries[1]["after"], serde_json::Value::Null);
+    assert!(DateTime::parse_from_rfc3339(entries[1]["timestamp"].as_str().unwrap()).is_ok());
+}
+
+// 覆寫資料文件並將修改時間設為未來，模擬外部編輯
+fn edit_externally(path: &Path, items: &[Item]) {
+    fs::write(path, serialize_data_file(items).unwrap()).unwrap();
+    let file = File::options().write(true).open(path).unwrap();
+    file.set_modified(SystemTime::now() + Duration::from_secs(60)).unwrap();
+}
+
+#[actix_web::test]
+async fn external_edit_does_not_lose_unflushed_changes() {
+    let dir = TempDir::new();
+    let path = dir.path("items.json");
+    save_items(&path, &items([1]), 0).unwrap();
+    let state = web::Data::new(state_with_file(items([1]), path.clone(), true));
+    let app = app!(state);
+
+    let req = TestRequest::post().uri("/items").set_json(serde_json::json!({"name": "unflushed"})).to_request();
+    assert_eq!(call_service(&app, req).await.status(), 201);
+    edit_externally(&path, &items([1, 7]));
+
+    let req = TestRequest::post().uri("/items").set_json(serde_json::json!({"name": "late"})).to_request();
+    assert_eq!(call_service(&app, req).await.status(), 409); // 不在過期的資料上繼續修改
+    assert!(state.flush_if_dirty().is_err()); // 不覆蓋外部的修改
+    assert!(state.dirty.load(Ordering::Acquire));
+
+    let resp = call_service(&app, TestRequest::post().uri("/admin/reload").to_request()).await;
+    assert_eq!(resp.status(), 409); // 尚未寫入的修改不會被默默捨棄
+    assert_eq!(state.read_items().unwrap().len(), 2);
+
+    let req = TestRequest::post().uri("/admin/reload?force=true").to_request();
+    let count: serde_json::Value = call_and_read_body_json(&app, req).await;
+    assert_eq!(count["count"], 2);
+    assert_eq!(state.read_items().unwrap().iter().map(|i| i.id).collect::<Vec<_>>(), [1, 7]);
+    assert!(!state.dirty.load(Ordering::Acquire));
+}
+
+#[actix_web::test]
+async fn autocomplete_matches_prefixes_up_to_the_limit() {
+    let stored = ["apple", "Apricot", "banana", "apple", "avocado"].iter().enumerate().map(|(n, name)| Item::new(n + 1, name.to_string())).collect();
+    let state = web::Data::new(test_state(stored));
+    let app = app!(state);
+
+    let names: Vec<String> = call_and_read_body_json(&app, TestRequest::get().uri("/items/autocomplete?prefix=ap").to_request()).await;
+    assert_eq!(names, ["apple", "Apricot"]); // 不分大小寫，重複的名稱只返回一次
+    let names: Vec<String> = call_and_read_body_json(&app, TestRequest::get().uri("/items/autocomplete?prefix=a&limit=2").to_request()).await;
+    assert_eq!(names, ["apple", "Apricot"]);
+    let names: Vec<String> = call_and_read_body_json(&app, TestRequest::get().uri("/items/autocomplete?prefix=z").to_request()).await;
+    assert!(names.is_empty());
+}
+
+#[actix_web::test]
+async fn origins_and_preflight_requests() {
+    let app = init_service(App::new().wrap(cors(&[], &[])).configure(|cfg| {
+        cfg.route("/items", web::post().to(HttpResponse::Created));
+    }))
+    .await;
+    let req = TestRequest::default()
+        .method(Method::OPTIONS)
+        .uri("/items")
+        .insert_header((header::ORIGIN, "http://example.com"))
+        .insert_header((header::ACCESS_CONTROL_REQUEST_METHOD, "POST"))
+        .to_request();
+    let resp = call_service(&app, req).await;
+    assert!(resp.status().is_success());
+    assert!(resp.headers().contains_key(header::ACCESS_CONTROL_ALLOW_ORIGIN));
+    assert!(resp.headers().contains_key(header::ACCESS_CONTROL_ALLOW_METHODS));
+}
+
+#[actix_web::test]
+async fn read_origins_cannot_preflight_writes() {
+    let read_origins = ["http://reader.example".to_string()];
+    let write_origins = ["http://writer.example".to_string()];
+    let app = init_service(App::new().wrap(cors(&read_origins, &write_origins)).configure(|cfg| {
+        cfg.route("/items", web::get().to(HttpResponse::Ok));
+    }))
+    .await;
+    let preflight = |origin: &str, method: &str| {
+        TestRequest::default()
+            .method(Method::OPTIONS)
+            .uri("/items")
+            .insert_header((header::ORIGIN, origin.to_string()))
+            .insert_header((header::ACCESS_CONTROL_REQUEST_METHOD, method.to_string()))
+            .to_request()
+    };
+
+    let resp = call_service(&app, preflight("http://writer.example", "POST")).await;
+    assert!(resp.status().is_success());
+    assert_eq!(resp.headers().get(header::ACCESS_CONTROL_ALLOW_ORIGIN).unwrap(), "http://writer.example");
+    let resp = call_service(&app, preflight("http://reader.example", "POST")).await;
+    assert!(!resp.status().is_success());
+    assert!(!resp.headers().contains_key(header::ACCESS_CONTROL_ALLOW_ORIGIN));
+    let resp = call_service(&app, preflight("http://reader.example", "GET")).await;
+    assert!(resp.status().is_success());
+    let resp = call_service(&app, preflight("http://other.example", "GET")).await;
+    assert!(!resp.status().is_success());
+}
+
+#[test]
+fn preflight_checks_the_requested_method() {
+    let preflight = |method: &str| {
+        TestRequest::default()
+            .method(Method::OPTIONS)
+            .insert_header((header::ACCESS_CONTROL_REQUEST_METHOD, method.to_string()))
+            .to_srv_request()
+    };
+    assert!(is_read_request(preflight("GET").head()));
+    assert!(!is_read_request(preflight("DELETE").head()));
+    assert!(is_read_request(TestRequest::get().to_srv_request().head()));
+    assert!(!is_read_request(TestRequest::post().to_srv_request().head()));
+}
+
+#[actix_web::test]
+async fn items_are_grouped_by_tag() {
+    let mut stored = items(1..=3);
+    stored[0].tags = vec!["red".to_string(), "blue".to_string()];
+    stored[1].tags = vec!["red".to_string(), "red".to_string()];
+    let state = web::Data::new(test_state(stored));
+    let app = app!(state);
+
+    let groups: BTreeMap<String, Vec<Item>> = call_and_read_body_json(&app, TestRequest::get().uri("/items/grouped-by-tag").to_request()).await;
+    assert_eq!(groups.keys().map(String::as_str).collect::<Vec<_>>(), [UNTAGGED_KEY, "blue", "red"]);
+    assert_eq!(ids(&groups["red"]), [1, 2]); // 重複的標籤只加入一次
+    assert_eq!(ids(&groups["blue"]), [1]);
+    assert_eq!(ids(&groups[UNTAGGED_KEY]), [3]);
+}
+
+#[actix_web::test]
+async fn non_numeric_id_returns_400() {
+    let state = web::Data::new(test_state(items([1])));
+    let app = app!(state);
+
+    for req in [TestRequest::get().uri("/items/abc"), TestRequest::delete().uri("/items/-1")] {
+        let resp = call_service(&app, req.to_request()).await;
+        assert_eq!(resp.status(), 400);
+        let body: serde_json::Value = serde_json::from_slice(&read_body(resp).await).unwrap();
+        assert_eq!(body["code"], "invalid_id");
+    }
+}
+
+#[actix_web::test]
+async fn creates_beyond_max_items_return_507() {
+    let mut state = test_state(items(1..=2));
+    state.max_items = Some(3);
+    let state = web::Data::new(state);
+    let app = app!(state);
+
+    let req = post_json("/items/bulk", serde_json::json!([{"name": "a"}, {"name": "b"}])).to_request();
+    assert_eq!(call_service(&app, req).await.status(), 507);
+    assert_eq!(state.read_items().unwrap().len(), 2); // 整批超過上限時全部不創建
+
+    let req = post_json("/items", serde_json::json!({"name": "third"})).to_request();
+    assert_eq!(call_service(&app, req).await.status(), 201);
+    let req = post_json("/items", serde_json::json!({"name": "fourth"})).to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), 507);
+    let body: serde_json::Value = serde_json::from_slice(&read_body(resp).await).unwrap();
+    assert_eq!(body["code"], "insufficient_storage");
+
+    let req = TestRequest::delete().uri("/items/3").to_request();
+    assert_eq!(call_service(&app, req).await.status(), 200);
+    let req = post_json("/items", serde_json::json!({"name": "fourth"})).to_request();
+    assert_eq!(call_service(&app, req).await.status(), 201); // 軟刪除的項目不佔用上限
+    let req = TestRequest::post().uri("/items/3/restore").to_request();
+    assert_eq!(call_service(&app, req).await.status(), 507); // 還原會超過上限
+}
+
+#[actix_web::test]
+async fn summary_omits_the_heavier_fields() {
+    let state = web::Data::new(test_state(items(1..=2)));
+    let app = app!(state);
+    let summaries: serde_json::Value = call_and_read_body_json(&app, TestRequest::get().uri("/items/summary").to_request()).await;
+    assert_eq!(summaries, serde_json::json!([{"id": 1, "name": "item-1"}, {"id": 2, "name": "item-2"}]));
+}
+
+#[test]
+fn socket_commands_and_event_frames() {
+    let state = test_state(items([1]));
+    let item: Item = serde_json::from_str(&socket_command("get 1", &state)).unwrap();
+    assert_eq!(item.id, 1);
+    let error: serde_json::Value = serde_json::from_str(&socket_command("get x", &state)).unwrap();
+    assert_eq!(error["code"], "validation_error");
+    let error: serde_json::Value = serde_json::from_str(&socket_command("delete 1", &state)).unwrap();
+    assert_eq!(error["code"], "validation_error");
+    let error: serde_json::Value = serde_json::from_str(&socket_command("get 9", &state)).unwrap();
+    assert_eq!(error["code"], "not_found");
+
+    let event = ItemEvent { kind: ItemEventKind::Created, id: 1 };
+    assert_eq!(serde_json::to_string(&event).unwrap(), r#"{"type":"created","id":1}"#); // 與 SSE 相同的訊息格式
+}
+
+#[test]
+fn v0_data_file_is_migrated_on_load() {
+    let dir = TempDir::new();
+    let path = dir.path("items.json");
+    fs::write(&path, r#"[{"id": 1, "name": "old"}]"#).unwrap();
+
+    let loaded = load_items(&path).unwrap();
+    assert_eq!(loaded[0].name, "old");
+    assert_eq!(loaded[0].version, 1);
+    let file: serde_json::Value = serde_json::from_slice(&fs::read(&path).unwrap()).unwrap();
+    assert_eq!(file["schema_version"], SCHEMA_VERSION);
+    assert_eq!(file["items"][0]["name"], "old");
+    assert_eq!(fs::read_to_string(dir.path("items.json.v0")).unwrap(), r#"[{"id": 1, "name": "old"}]"#); // 保留原本的文件
+}
+
+#[actix_web::test]
+async fn large_deletes_require_confirmation() {
+    let mut state = test_state(items(1..=5));
+    state.bulk_delete_confirm_ratio = 0.5;
+    let state = web::Data::new(state);
+    let app = app!(state);
+
+    let ids = serde_json::json!({"ids": [1, 2, 3, 4]}); // 80% 的項目
+    let req = TestRequest::post().uri("/items/bulk-delete").set_json(&ids).to_request();
+    assert_eq!(call_service(&app, req).await.status(), 400);
+    let file = serde_json::to_string(&items([5])).unwrap();
+    assert_eq!(call_service(&app, upload("/items/import?mode=replace", &file).to_request()).await.status(), 400);
+    assert!(state.read_items().unwrap().iter().all(|i| !i.deleted));
+
+    let req = TestRequest::post().uri("/items/bulk-delete").set_json(serde_json::json!({"ids": [1, 2]})).to_request();
+    assert_eq!(call_service(&app, req).await.status(), 200); // 40% 不需確認
+    let req = TestRequest::post().uri("/items/bulk-delete?confirm=true").set_json(&ids).to_request();
+    let count: serde_json::Value = call_and_read_body_json(&app, req).await;
+    assert_eq!(count["count"], 2);
+    let resp = call_service(&app, upload("/items/import?mode=replace&confirm=true", &file).to_request()).await;
+    assert_eq!(resp.status(), 200);
+    let resp = call_service(&app, TestRequest::delete().uri("/items").to_request()).await;
+    assert_eq!(resp.status(), 204); // 清空所有項目不需確認
+    assert!(state.read_items().unwrap().iter().all(|i| i.deleted));
+}
+
+#[actix_web::test]
+async fn options_list_the_supported_methods() {
+    let state = web::Data::new(test_state(items([1])));
+    let app = app!(state);
+
+    let resp = call_service(&app, TestRequest::default().method(Method::OPTIONS).uri("/items").to_request()).await;
+    assert_eq!(resp.status(), 200);
+    let allow = resp.headers().get(header::ALLOW).unwrap().to_str().unwrap().to_string();
+    assert!(allow.split(", ").any(|m| m == "GET"));
+    assert!(allow.split(", ").any(|m| m == "POST"));
+    let resp = call_service(&app, TestRequest::default().method(Method::OPTIONS).uri("/items/1").to_request()).await;
+    assert!(resp.headers().get(header::ALLOW).unwrap().to_str().unwrap().contains("PATCH"));
+
+    let mut read_only = test_state(vec![]);
+    read_only.read_only = true;
+    let state = web::Data::new(read_only);
+    let app = app!(state);
+    let resp = call_service(&app, TestRequest::default().method(Method::OPTIONS).uri("/items").to_request()).await;
+    assert_eq!(resp.headers().get(header::ALLOW).unwrap(), "GET, HEAD, OPTIONS");
+}
+
+#[test]
+fn name_normalization_collapses_whitespace_and_lowercases() {
+    let mut name = "  Foo   Bar  ".to_string();
+    NameNormalization { collapse_whitespace: true, lowercase: false }.apply(&mut name);
+    assert_eq!(name, "Foo Bar");
+    NameNormalization { collapse_whitespace: true, lowercase: true }.apply(&mut name);
+    assert_eq!(name, "foo bar");
+}
+
+#[actix_web::test]
+async fn created_and_patched_names_are_normalized() {
+    let mut state = test_state(vec![]);
+    state.name_normalization = NameNormalization { collapse_whitespace: true, lowercase: true };
+    let state = web::Data::new(state);
+    let app = app!(state);
+
+    let created: Item = call_and_read_body_json(&app, post_json("/items", serde_json::json!({"name": "  New   Item "})).to_request()).await;
+    assert_eq!(created.name, "new item");
+    let req = TestRequest::patch().uri("/items/1").set_json(serde_json::json!({"name": "Patched\tName"})).to_request();
+    let patched: Item = call_and_read_body_json(&app, req).await;
+    assert_eq!(patched.name, "patched name");
+}
+
+#[actix_web::test]
+async fn reorder_moves_an_item_to_the_front() {
+    let state = web::Data::new(test_state(items(1..=4)));
+    let app = app!(state);
+
+    let req = post_json("/items/3/reorder", serde_json::json!({"position": 0})).to_request();
+    assert_eq!(call_service(&app, req).await.status(), 200);
+    let listed: Vec<Item> = call_and_read_body_json(&app, TestRequest::get().uri("/items").to_request()).await;
+    assert_eq!(ids(&listed), [3, 1, 2, 4]);
+    let req = post_json("/items/3/reorder", serde_json::json!({"position": 10})).to_request();
+    assert_eq!(call_service(&app, req).await.status(), 200);
+    let listed: Vec<Item> = call_and_read_body_json(&app, TestRequest::get().uri("/items").to_request()).await;
+    assert_eq!(ids(&listed), [1, 2, 4, 3]); // 超過項目數時移到最後
+    assert_eq!(state.read_items().unwrap().get(3).map(|i| i.id), Some(3)); // 索引與順序一致
+}
+
+#[test]
+fn flush_if_dirty_saves_and_keeps_the_flag_on_failure() {
+    let dir = TempDir::new();
+    let state = state_with_file(items(1..=2), dir.path("items.json"), true);
+    state.mark_dirty();
+    state.flush_if_dirty().unwrap();
+    assert!(!state.dirty.load(Ordering::Acquire));
+    assert!(state.items.try_write().is_ok()); // 寫入完成後沒有殘留的鎖
+    let (saved, _) = parse_data_file(&fs::read(dir.path("items.json")).unwrap()).unwrap();
+    assert_eq!(saved.len(), 2);
+
+    let state = state_with_file(items(1..=2), dir.0.clone(), true); // 資料文件的路徑是目錄，寫入必定失敗
+    state.mark_dirty();
+    assert!(state.flush_if_dirty().is_err());
+    assert!(state.dirty.load(Ordering::Acquire)); // 保留標記以便下次重試
+}
+
+#[test]
+fn concurrent_saves_are_not_seen_as_external_edits() {
+    let dir = TempDir::new();
+    let state = state_with_file(items(1..=2), dir.path("items.json"), true);
+    state.mark_dirty();
+    state.flush_if_dirty().unwrap();
+    std::thread::scope(|scope| {
+        scope.spawn(|| {
+            for _ in 0..200 {
+                state.mark_dirty();
+                state.flush_if_dirty().unwrap();
+            }
+        });
+        for _ in 0..200 {
+            assert!(state.write_items().is_ok()); // 伺服器自己寫入的文件不應觸發 409
+        }
+    });
+}
+
+#[cfg(not(feature = "sqlite"))]
+#[test]
+fn database_url_requires_the_sqlite_feature() {
+    let _env = env_lock();
+    std::env::set_var("DATABASE_URL", "sqlite:items.db");
+    let error = ServerConfig::from_env().err().unwrap();
+    std::env::remove_var("DATABASE_URL");
+    assert!(error.to_string().contains("--features sqlite"));
+    assert!(ServerConfig::from_env().unwrap().database_url.is_none());
+}
+
+#[test]
+fn database_url_rejects_json_only_settings() {
+    let _env = env_lock();
+    std::env::set_var("DATABASE_URL", "sqlite:items.db");
+    for name in ["MAX_ITEMS", "UNIQUE_NAMES", "NORMALIZE_NAMES"] {
+        std::env::set_var(name, if name == "MAX_ITEMS" { "10" } else { "true" });
+        let error = ServerConfig::from_env().err().unwrap();
+        std::env::remove_var(name);
+        assert!(error.to_string().contains(&format!("{} is not supported with DATABASE_URL", name)));
+    }
+    std::env::set_var("UNIQUE_NAMES", "false");
+    let error = ServerConfig::from_env().err().map(|e| e.to_string());
+    std::env::remove_var("UNIQUE_NAMES");
+    std::env::remove_var("DATABASE_URL");
+    assert!(!error.unwrap_or_default().contains("not supported with DATABASE_URL")); // 停用的設定不影響 SQLite 後端
+}
+
+#[actix_web::test]
+async fn soft_deleted_items_are_hidden_from_reads() {
+    let mut stored = items(1..=3);
+    stored[1] = deleted(stored[1].clone());
+    let state = web::Data::new(test_state(stored));
+    let app = app!(state);
+
+    let count: serde_json::Value = call_and_read_body_json(&app, TestRequest::get().uri("/items/count").to_request()).await;
+    assert_eq!(count["count"], 2);
+    let found: Vec<Item> = call_and_read_body_json(&app, TestRequest::get().uri("/items/search?q=item").to_request()).await;
+    assert_eq!(found.iter().map(|i| i.id).collect::<Vec<_>>(), [1, 3]);
+    let batch: Vec<Item> = call_and_read_body_json(&app, TestRequest::get().uri("/items/batch?ids=2,3").to_request()).await;
+    assert_eq!(batch.iter().map(|i| i.id).collect::<Vec<_>>(), [3]);
+    let csv = call_and_read_body(&app, TestRequest::get().uri("/items.csv").to_request()).await;
+    assert_eq!(csv, "id,name\r\n1,item-1\r\n3,item-3\r\n");
+    let ndjson = call_and_read_body(&app, TestRequest::get().uri("/items/export").to_request()).await;
+    assert_eq!(ndjson.split(|b| *b == b'\n').filter(|line| !line.is_empty()).count(), 2);
+    let body = call_and_read_body(&app, TestRequest::get().uri("/metrics").to_request()).await;
+    assert!(String::from_utf8_lossy(&body).contains("items_total 2\n"));
+
+    let resp = call_service(&app, TestRequest::get().uri("/items/2").to_request()).await;
+    assert_eq!(resp.status(), 404);
+    let resp = call_service(&app, TestRequest::get().uri("/items/2/children").to_request()).await;
+    assert_eq!(resp.status(), 404);
+    let req = TestRequest::patch().uri("/items/2").set_json(serde_json::json!({"name": "x"})).to_request();
+    assert_eq!(call_service(&app, req).await.status(), 404);
+    let req = TestRequest::put().uri("/items/2?upsert=true").set_json(Item::new(2, "x".to_string())).to_request();
+    assert_eq!(call_service(&app, req).await.status(), 409);
+    assert!(socket_command("get 2", &state).contains(ApiError::NotFound.code()));
+}
+
+#[actix_web::test]
+async fn delete_all_and_replace_import_are_soft() {
+    let state = web::Data::new(test_state(items(1..=3)));
+    let app = app!(state);
+
+    let file = serde_json::to_string(&items([1])).unwrap();
+    let resp = call_service(&app, upload("/items/import?mode=replace", &file).to_request()).await;
+    assert_eq!(resp.status(), 200);
+    assert_eq!(state.read_items().unwrap().iter().filter(|i| i.deleted).map(|i| i.id).collect::<Vec<_>>(), [2, 3]);
+
+    let resp = call_service(&app, TestRequest::delete().uri("/items").to_request()).await;
+    assert_eq!(resp.status(), 204);
+    assert_eq!(state.read_items().unwrap().len(), 3);
+    assert!(state.read_items().unwrap().iter().all(|i| i.deleted));
+
+    let resp = call_service(&app, TestRequest::post().uri("/items/2/restore").to_request()).await;
+    assert_eq!(resp.status(), 200);
+    let listed: Vec<Item> = call_and_read_body_json(&app, TestRequest::get().uri("/items").to_request()).await;
+    assert_eq!(listed.iter().map(|i| i.id).collect::<Vec<_>>(), [2]);
+}
+
+#[actix_web::test]
+async fn admin_endpoints_require_the_api_key_when_set() {
+    let paths = api_doc("", false, false).paths.paths;
+    assert!(paths.contains_key("/admin/maintenance"));
+    assert!(paths.contains_key("/admin/reload"));
+
+    let mode = web::Data::new(MaintenanceMode::new(false));
+    let app = init_service(App::new().wrap(ApiKey::new(Some("secret".to_string()))).app_data(mode.clone()).service(set_maintenance)).await;
+    let enable = || TestRequest::post().uri("/admin/maintenance").set_json(serde_json::json!({"enabled": true}));
+    assert_eq!(call_service(&app, enable().to_request()).await.status(), 401);
+    let req = enable().insert_header(("X-API-Key", "secret")).to_request();
+    assert_eq!(call_service(&app, req).await.status(), 200);
+
+    let app = init_service(App::new().wrap(ApiKey::new(None)).app_data(mode.clone()).service(set_maintenance)).await;
+    assert_eq!(call_service(&app, enable().to_request()).await.status(), 200); // 未設定 API_KEY 時仍然註冊
+}
+
+#[actix_web::test]
+async fn import_validates_parent_links_of_the_merged_items() {
+    let state = web::Data::new(test_state(items(1..=2)));
+    let app = app!(state);
+
+    let file = serde_json::to_string(&[child(3, 1), child(4, 3)]).unwrap(); // 父項目可以是現有項目或文件中的項目
+    let resp = call_service(&app, upload("/items/import", &file).to_request()).await;
+    assert_eq!(resp.status(), 200);
+
+    let file = serde_json::to_string(&[child(5, 99)]).unwrap();
+    let resp = call_service(&app, upload("/items/import", &file).to_request()).await;
+    assert_eq!(resp.status(), 400);
+
+    let file = serde_json::to_string(&[child(1, 4)]).unwrap(); // 1 -> 4 -> 3 -> 1
+    let resp = call_service(&app, upload("/items/import", &file).to_request()).await;
+    assert_eq!(resp.status(), 400);
+
+    let file = serde_json::to_string(&[child(3, 2)]).unwrap(); // 取代模式下 2 會被刪除，不能作為父項目
+    let resp = call_service(&app, upload("/items/import?mode=replace", &file).to_request()).await;
+    assert_eq!(resp.status(), 400);
+    assert_eq!(state.read_items().unwrap().len(), 4); // 失敗時不修改任何項目
+}
+
+#[actix_web::test]
+async fn every_write_path_normalizes_names() {
+    let mut state = test_state(vec![]);
+    state.name_normalization = NameNormalization { collapse_whitespace: true, lowercase: true };
+    state.defaults = ItemDefaults { tags: vec!["default".to_string()], name_prefix: None };
+    let state = web::Data::new(state);
+    let app = app!(state);
+
+    let file = serde_json::to_string(&[Item::new(1, "  Imported   Item ".to_string())]).unwrap();
+    assert_eq!(call_service(&app, upload("/items/import", &file).to_request()).await.status(), 200);
+    let req = TestRequest::post()
+        .uri("/items/import-ndjson")
+        .insert_header((header::CONTENT_TYPE, "application/x-ndjson"))
+        .set_payload("{\"name\": \" Streamed  Item\"}\n")
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 200);
+    let req = TestRequest::post()
+        .uri("/items/bulk-rename")
+        .set_json(serde_json::json!({"prefix": "streamed", "replacement": "  Renamed  "}))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 200);
+
+    let items = state.read_items().unwrap();
+    assert_eq!(items.iter().map(|i| i.name.as_str()).collect::<Vec<_>>(), ["imported item", "renamed item"]);
+    assert_eq!(items[1].tags, ["default"]); // NDJSON 匯入也套用預設標籤
+}
+
+#[actix_web::test]
+async fn max_items_applies_to_every_way_of_adding_items() {
+    let mut state = test_state(items(1..=2));
+    state.max_items = Some(3);
+    let state = web::Data::new(state);
+    let app = app!(state);
+
+    let req = TestRequest::post().uri("/items").set_json(serde_json::json!({"name": "third"})).to_request();
+    assert_eq!(call_service(&app, req).await.status(), 201);
+    let req = TestRequest::post().uri("/items").set_json(serde_json::json!({"name": "fourth"})).to_request();
+    assert_eq!(call_service(&app, req).await.status(), 507);
+
+    let req = TestRequest::put().uri("/items/9?upsert=true").set_json(Item::new(9, "upsert".to_string())).to_request();
+    assert_eq!(call_service(&app, req).await.status(), 507);
+
+    let file = serde_json::to_string(&items([1, 9])).unwrap(); // 更新現有的 1 不計入，新增的 9 會超過上限
+    assert_eq!(call_service(&app, upload("/items/import", &file).to_request()).await.status(), 507);
+    let file = serde_json::to_string(&items([1])).unwrap();
+    assert_eq!(call_service(&app, upload("/items/import", &file).to_request()).await.status(), 200);
+
+    let req = TestRequest::post()
+        .uri("/items/import-ndjson")
+        .insert_header((header::CONTENT_TYPE, "application/x-ndjson"))
+        .set_payload("{\"name\": \"a\"}\n{\"name\": \"b\"}\n")
+        .to_request();
+    let result: serde_json::Value = call_and_read_body_json(&app, req).await;
+    assert_eq!(result["imported"], 0);
+    let failed_lines = if cfg!(feature = "camel-case") { "failedLines" } else { "failed_lines" };
+    assert_eq!(result[failed_lines], serde_json::json!([1, 2]));
+    assert_eq!(state.read_items().unwrap().len(), 3);
+}
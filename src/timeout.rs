@@ -0,0 +1,116 @@
+// 請求逾時中介軟體：處理函式超過 REQUEST_TIMEOUT_MS 仍未完成時放棄等待並返回 503
+// 讓客戶端在磁碟緩慢等情況下快速失敗，逾時在處理函式等待（await）時生效，無法中斷同步執行中的程式碼，
+// 因此處理函式中的磁碟讀寫需透過 web::block 執行；上傳及串流的服務本來就會持續較久，不受逾時限制
+use actix_web::dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::rt::time::timeout;
+use actix_web::error::InternalError;
+use actix_web::{Error, HttpResponse};
+use std::future::{ready, Future, Ready};
+use std::pin::Pin;
+use std::time::Duration;
+
+use crate::ErrorResponse;
+
+// 不限制時間的服務：上傳文件、NDJSON 匯入、SSE、WebSocket 及 NDJSON 匯出，路徑可能帶有 API_PREFIX
+fn is_exempt(path: &str) -> bool {
+    ["/items/import", "/items/import-ndjson", "/items/events", "/ws", "/items/export"]
+        .iter()
+        .any(|suffix| path.ends_with(suffix))
+}
+
+#[derive(Clone)]
+pub struct RequestTimeout {
+    limit: Option<Duration>, // 每個請求的時間上限，為 None 時不限制
+}
+
+impl RequestTimeout {
+    pub fn new(limit: Option<Duration>) -> Self {
+        RequestTimeout { limit }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for RequestTimeout
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = RequestTimeoutMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RequestTimeoutMiddleware {
+            service,
+            limit: self.limit,
+        }))
+    }
+}
+
+pub struct RequestTimeoutMiddleware<S> {
+    service: S,
+    limit: Option<Duration>,
+}
+
+impl<S, B> Service<ServiceRequest> for RequestTimeoutMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let limit = self.limit.filter(|_| !is_exempt(req.path()));
+        let fut = self.service.call(req);
+        let Some(limit) = limit else {
+            return Box::pin(fut);
+        };
+
+        Box::pin(async move {
+            match timeout(limit, fut).await {
+                Ok(res) => res,
+                Err(_) => {
+                    // 原本的請求已隨處理函式一起被丟棄，以錯誤返回響應，由 actix-web 轉換為 503
+                    let message = format!("request did not complete within {}ms", limit.as_millis());
+                    log::warn!("{}", message);
+                    let response = HttpResponse::ServiceUnavailable().json(ErrorResponse::new("request_timeout", &*message));
+                    Err(InternalError::from_response(message, response).into()) // 返回 503 Service Unavailable 響應
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::test::{call_service, init_service, try_call_service, TestRequest};
+    use actix_web::{web, App};
+
+    // 模擬緩慢的磁碟寫入，與處理函式相同透過 web::block 執行
+    async fn slow_save() -> Result<HttpResponse, Error> {
+        web::block(|| std::thread::sleep(Duration::from_millis(300))).await?;
+        Ok(HttpResponse::Ok().finish())
+    }
+
+    #[actix_web::test]
+    async fn slow_save_times_out() {
+        let app = init_service(
+            App::new()
+                .wrap(RequestTimeout::new(Some(Duration::from_millis(50))))
+                .route("/items", web::post().to(slow_save))
+                .route("/items/import", web::post().to(slow_save)),
+        )
+        .await;
+
+        let err = try_call_service(&app, TestRequest::post().uri("/items").to_request()).await.err().unwrap();
+        assert_eq!(err.error_response().status(), 503);
+        let resp = call_service(&app, TestRequest::post().uri("/items/import").to_request()).await;
+        assert_eq!(resp.status(), 200); // 上傳文件的服務不受逾時限制
+    }
+}
@@ -0,0 +1,35 @@
+// 從 PEM 文件載入 TLS 憑證及私鑰，讓伺服器直接提供 HTTPS
+use std::fs::File;
+use std::io::{self, BufReader};
+use std::path::Path;
+
+use crate::TlsPaths;
+
+// 開啟 PEM 文件，失敗時在錯誤中附上路徑
+fn open_pem(path: &Path) -> io::Result<BufReader<File>> {
+    File::open(path)
+        .map(BufReader::new)
+        .map_err(|e| io::Error::new(e.kind(), format!("failed to open {}: {}", path.display(), e)))
+}
+
+// 建立 rustls 的伺服器設定，文件內容無效時返回錯誤
+pub fn server_config(paths: &TlsPaths) -> io::Result<rustls::ServerConfig> {
+    let certs = rustls_pemfile::certs(&mut open_pem(&paths.cert)?).collect::<Result<Vec<_>, _>>()?;
+    if certs.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("no certificates found in {}", paths.cert.display()),
+        ));
+    }
+    let key = rustls_pemfile::private_key(&mut open_pem(&paths.key)?)?.ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("no private key found in {}", paths.key.display()),
+        )
+    })?;
+
+    rustls::ServerConfig::builder()
+        .with_no_client_auth()
+        .with_single_cert(certs, key)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}